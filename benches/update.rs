@@ -0,0 +1,92 @@
+use cloth_model::simulation::{Cloth, Integrator, PinPattern};
+use cloth_model::vec3::{Scalar, Vec3};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const SIZES: [usize; 3] = [10, 50, 100];
+const STEPS: usize = 10;
+const DT: Scalar = 0.01;
+
+fn hanging_cloth(size: usize) -> Cloth {
+    let mut cloth = Cloth::new(size, size, 1.0, 100.0);
+    cloth.apply_pin_pattern(PinPattern::TopCorners);
+    cloth
+}
+
+fn bench_update(c: &mut Criterion) {
+    let wind = Vec3::new(1.0, 0.0, 1.0);
+    let mut group = c.benchmark_group("update_10_steps");
+    for size in SIZES {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{size}x{size}")),
+            &size,
+            |b, &size| {
+                b.iter_batched(
+                    || hanging_cloth(size),
+                    |mut cloth| {
+                        for _ in 0..STEPS {
+                            cloth.update(DT, wind, 10.0);
+                        }
+                        cloth
+                    },
+                    criterion::BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+// Force evaluation on its own, without integration or constraints
+fn bench_forces(c: &mut Criterion) {
+    let wind = Vec3::new(1.0, 0.0, 1.0);
+    let mut group = c.benchmark_group("forces");
+    for size in SIZES {
+        let cloth = hanging_cloth(size);
+        let positions: Vec<Vec3> = cloth.masses.iter().map(|mass| mass.position).collect();
+        let velocities: Vec<Vec3> = cloth.masses.iter().map(|mass| mass.velocity).collect();
+        group.bench_function(BenchmarkId::from_parameter(format!("{size}x{size}")), |b| {
+            b.iter(|| cloth.compute_accelerations(black_box(&positions), &velocities, wind, 10.0))
+        });
+    }
+    group.finish();
+}
+
+// Integration on its own, stepping every mass with forces computed up front
+fn bench_integration(c: &mut Criterion) {
+    let wind = Vec3::new(1.0, 0.0, 1.0);
+    let mut group = c.benchmark_group("integration");
+    for size in SIZES {
+        let cloth = hanging_cloth(size);
+        let positions: Vec<Vec3> = cloth.masses.iter().map(|mass| mass.position).collect();
+        let velocities: Vec<Vec3> = cloth.masses.iter().map(|mass| mass.velocity).collect();
+        let forces: Vec<Vec3> = cloth
+            .compute_accelerations(&positions, &velocities, wind, 10.0)
+            .into_iter()
+            .zip(&cloth.masses)
+            .map(|(acceleration, mass)| acceleration * mass.mass())
+            .collect();
+        for integrator in [Integrator::Euler, Integrator::Verlet] {
+            group.bench_with_input(
+                BenchmarkId::new(format!("{integrator:?}"), format!("{size}x{size}")),
+                &integrator,
+                |b, &integrator| {
+                    b.iter_batched(
+                        || cloth.masses.clone(),
+                        |mut masses| {
+                            for (mass, force) in masses.iter_mut().zip(&forces) {
+                                mass.apply_force(*force);
+                                mass.update(DT, integrator, None);
+                            }
+                            masses
+                        },
+                        criterion::BatchSize::LargeInput,
+                    )
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_update, bench_forces, bench_integration);
+criterion_main!(benches);
@@ -0,0 +1,185 @@
+use crate::defaults::{
+    DEFAULT_COLS, DEFAULT_DAMPING, DEFAULT_GRAVITY, DEFAULT_ROWS, DEFAULT_SPACING,
+    DEFAULT_STIFFNESS,
+};
+use crate::simulation::{Cloth, Integrator, Orientation, PinPattern, SpringStiffness};
+use crate::vec3::{Scalar, Vec3};
+
+pub struct ClothBuilder {
+    cols: usize,
+    rows: usize,
+    spacing: Scalar,
+    stiffness: SpringStiffness,
+    gravity: Vec3,
+    damping: Scalar,
+    spring_damping: Scalar,
+    integrator: Integrator,
+    solver_iterations: usize,
+    tear_threshold: Option<Scalar>,
+    pin_pattern: PinPattern,
+    orientation: Orientation,
+}
+
+impl ClothBuilder {
+    pub fn new() -> Self {
+        ClothBuilder {
+            cols: DEFAULT_COLS,
+            rows: DEFAULT_ROWS,
+            spacing: DEFAULT_SPACING,
+            stiffness: SpringStiffness::uniform(DEFAULT_STIFFNESS),
+            gravity: DEFAULT_GRAVITY,
+            damping: DEFAULT_DAMPING,
+            spring_damping: 0.0,
+            integrator: Integrator::Euler,
+            solver_iterations: 1,
+            tear_threshold: None,
+            pin_pattern: PinPattern::None,
+            orientation: Orientation::Horizontal,
+        }
+    }
+
+    pub fn dimensions(mut self, cols: usize, rows: usize) -> Self {
+        self.cols = cols;
+        self.rows = rows;
+        self
+    }
+
+    pub fn spacing(mut self, spacing: Scalar) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    pub fn stiffness(mut self, stiffness: impl Into<SpringStiffness>) -> Self {
+        self.stiffness = stiffness.into();
+        self
+    }
+
+    pub fn gravity(mut self, gravity: Vec3) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    pub fn damping(mut self, damping: Scalar) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    pub fn spring_damping(mut self, damping: Scalar) -> Self {
+        self.spring_damping = damping;
+        self
+    }
+
+    pub fn integrator(mut self, integrator: Integrator) -> Self {
+        self.integrator = integrator;
+        self
+    }
+
+    pub fn solver_iterations(mut self, iterations: usize) -> Self {
+        self.solver_iterations = iterations;
+        self
+    }
+
+    pub fn tear_threshold(mut self, strain: Scalar) -> Self {
+        self.tear_threshold = Some(strain);
+        self
+    }
+
+    pub fn pin_pattern(mut self, pattern: PinPattern) -> Self {
+        self.pin_pattern = pattern;
+        self
+    }
+
+    pub fn orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    pub fn build(self) -> Cloth {
+        let mut cloth = Cloth::new_oriented(
+            self.cols,
+            self.rows,
+            self.spacing,
+            self.stiffness,
+            self.orientation,
+        );
+        cloth.set_gravity(self.gravity);
+        cloth.set_damping(self.damping);
+        cloth.set_spring_damping(self.spring_damping);
+        cloth.set_integrator(self.integrator);
+        cloth.set_solver_iterations(self.solver_iterations);
+        if let Some(strain) = self.tear_threshold {
+            cloth.set_tear_threshold(strain);
+        }
+        cloth.apply_pin_pattern(self.pin_pattern);
+        cloth
+    }
+}
+
+impl Default for ClothBuilder {
+    fn default() -> Self {
+        ClothBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_build_a_flat_unpinned_sheet() {
+        let cloth = ClothBuilder::new().build();
+
+        assert_eq!((cloth.cols(), cloth.rows()), (DEFAULT_COLS, DEFAULT_ROWS));
+        assert_eq!(cloth.gravity(), DEFAULT_GRAVITY);
+        assert_eq!(cloth.damping(), DEFAULT_DAMPING);
+        assert_eq!(cloth.integrator(), Integrator::Euler);
+        assert_eq!(cloth.solver_iterations(), 1);
+        assert!(cloth.masses.iter().all(|mass| !mass.is_pinned() && mass.position.y == 0.0));
+        for spring in &cloth.structural_springs {
+            assert_eq!(spring.rest_length, DEFAULT_SPACING);
+            assert_eq!(spring.stiffness, DEFAULT_STIFFNESS);
+            assert_eq!(spring.damping, 0.0);
+            assert_eq!(spring.max_strain, None);
+        }
+    }
+
+    #[test]
+    fn every_option_overrides_its_default() {
+        let stiffness = SpringStiffness {
+            structural: 50.0,
+            shear: 20.0,
+            bend: 5.0,
+        };
+        let cloth = ClothBuilder::new()
+            .dimensions(4, 3)
+            .spacing(0.5)
+            .stiffness(stiffness)
+            .gravity(Vec3::new(0.0, -1.0, 0.0))
+            .damping(0.1)
+            .spring_damping(2.0)
+            .integrator(Integrator::Verlet)
+            .solver_iterations(5)
+            .tear_threshold(0.2)
+            .pin_pattern(PinPattern::TopEdge)
+            .orientation(Orientation::Vertical)
+            .build();
+
+        assert_eq!((cloth.cols(), cloth.rows()), (4, 3));
+        assert_eq!(cloth.gravity(), Vec3::new(0.0, -1.0, 0.0));
+        assert_eq!(cloth.damping(), 0.1);
+        assert_eq!(cloth.integrator(), Integrator::Verlet);
+        assert_eq!(cloth.solver_iterations(), 5);
+        for spring in &cloth.structural_springs {
+            assert_eq!(spring.rest_length, 0.5);
+            assert_eq!(spring.stiffness, 50.0);
+            assert_eq!(spring.damping, 2.0);
+            assert_eq!(spring.max_strain, Some(0.2));
+        }
+        assert!(cloth.shear_springs().iter().all(|spring| spring.stiffness == 20.0));
+        assert!(cloth.bend_springs().iter().all(|spring| spring.stiffness == 5.0));
+        for (index, mass) in cloth.masses.iter().enumerate() {
+            assert_eq!(mass.is_pinned(), index < 4);
+            assert_eq!(mass.position.z, 0.0);
+        }
+    }
+}
@@ -1,4 +1,12 @@
+use cloth_model::vec3::Vec3 as ModelVec3;
 use macroquad::prelude::*;
+use std::f32::consts::FRAC_PI_2;
+
+const ORBIT_SPEED: f32 = 2.0;
+const ZOOM_SPEED: f32 = 0.1;
+const MIN_DISTANCE: f32 = 2.0;
+const MAX_DISTANCE: f32 = 200.0;
+const MAX_ELEVATION: f32 = FRAC_PI_2 - 0.01;
 
 pub struct Camera {
     pub position: Vec3,
@@ -37,10 +45,58 @@ impl Camera {
             self.position += right * move_speed;
         }
 
+        // Orbit around the target while dragging with the left mouse button
+        let mouse_delta = mouse_delta_position();
+        if is_mouse_button_down(MouseButton::Left) {
+            self.orbit(mouse_delta.x * ORBIT_SPEED, -mouse_delta.y * ORBIT_SPEED);
+        }
+
+        let (_, scroll) = mouse_wheel();
+        if scroll != 0.0 {
+            self.zoom(-scroll.signum() * ZOOM_SPEED);
+        }
+
         // Update aspect ratio in case window was resized
         self.aspect = screen_width() / screen_height();
     }
 
+    pub fn orbit(&mut self, d_azimuth: f32, d_elevation: f32) {
+        let offset = ModelVec3::from(self.position - self.target);
+        let (distance, azimuth, elevation) = offset.to_spherical();
+        if distance == 0.0 {
+            return;
+        }
+
+        let azimuth = azimuth + d_azimuth;
+        let elevation = (elevation + d_elevation).clamp(-MAX_ELEVATION, MAX_ELEVATION);
+        self.position =
+            self.target + Vec3::from(ModelVec3::from_spherical(distance, azimuth, elevation));
+    }
+
+    pub fn zoom(&mut self, amount: f32) {
+        let offset = self.position - self.target;
+        let distance = (offset.length() * (1.0 + amount)).clamp(MIN_DISTANCE, MAX_DISTANCE);
+        self.position = self.target + self.view_direction() * distance;
+    }
+
+    pub fn frame(&mut self, min: Vec3, max: Vec3) {
+        let direction = self.view_direction();
+        let radius = (max - min).length() / 2.0;
+        self.target = (min + max) / 2.0;
+        self.position = self.target + direction * framing_distance(radius, self.fovy);
+    }
+
+    // From the target towards the camera, looking down the diagonal when the
+    // camera sits on the target
+    fn view_direction(&self) -> Vec3 {
+        let direction = (self.position - self.target).normalize_or_zero();
+        if direction == Vec3::ZERO {
+            vec3(1.0, 1.0, 1.0).normalize()
+        } else {
+            direction
+        }
+    }
+
     pub fn set_active(&self) {
         set_camera(&Camera3D {
             position: self.position,
@@ -51,4 +107,89 @@ impl Camera {
             ..Default::default()
         });
     }
-}
\ No newline at end of file
+}
+
+pub fn framing_distance(radius: f32, fovy: f32) -> f32 {
+    // macroquad feeds fovy straight into the projection, so use the same tangent it does
+    let distance = radius / (fovy / 2.0).tan().abs();
+    distance.clamp(MIN_DISTANCE, MAX_DISTANCE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Built by hand, since Camera::new asks the window for its aspect ratio
+    fn camera_at(position: Vec3) -> Camera {
+        Camera {
+            position,
+            target: Vec3::ZERO,
+            up: vec3(0.0, 1.0, 0.0),
+            fovy: 45.0,
+            aspect: 1.0,
+        }
+    }
+
+    #[test]
+    fn orbit_turns_around_the_target_at_a_fixed_distance() {
+        let mut camera = camera_at(vec3(10.0, 0.0, 0.0));
+        camera.orbit(FRAC_PI_2, 0.0);
+        assert!(camera.position.abs_diff_eq(vec3(0.0, 0.0, 10.0), 1e-4));
+
+        camera.orbit(0.0, 0.5);
+        assert!((camera.position.length() - 10.0).abs() < 1e-4);
+        assert!((camera.position.y - 10.0 * 0.5f32.sin()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn orbit_stops_short_of_the_poles() {
+        let mut camera = camera_at(vec3(0.0, 0.0, 5.0));
+        camera.orbit(0.0, 10.0);
+        assert!(camera.position.y < 5.0);
+        assert!((camera.position.y - 5.0 * MAX_ELEVATION.sin()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn zoom_scales_the_distance_within_its_limits() {
+        let mut camera = camera_at(vec3(0.0, 0.0, 10.0));
+        camera.zoom(0.5);
+        assert!(camera.position.abs_diff_eq(vec3(0.0, 0.0, 15.0), 1e-4));
+
+        camera.zoom(100.0);
+        assert!((camera.position.length() - MAX_DISTANCE).abs() < 1e-3);
+        camera.zoom(-0.99);
+        assert!((camera.position.length() - MIN_DISTANCE).abs() < 1e-4);
+    }
+
+    #[test]
+    fn zoom_from_the_target_backs_off_along_the_diagonal() {
+        let mut camera = camera_at(Vec3::ZERO);
+        camera.zoom(0.1);
+        assert!(camera.position.is_finite());
+        assert!(camera
+            .position
+            .abs_diff_eq(vec3(1.0, 1.0, 1.0).normalize() * MIN_DISTANCE, 1e-4));
+    }
+
+    #[test]
+    fn framing_distance_fits_the_radius_in_view() {
+        let fovy = 1.0;
+        let distance = framing_distance(10.0, fovy);
+        assert!((distance * (fovy / 2.0).tan() - 10.0).abs() < 1e-4);
+        assert_eq!(framing_distance(0.01, fovy), MIN_DISTANCE);
+        assert_eq!(framing_distance(1e6, fovy), MAX_DISTANCE);
+    }
+
+    #[test]
+    fn frame_centers_the_bounds_and_keeps_the_view_direction() {
+        let mut camera = camera_at(vec3(0.0, 0.0, 10.0));
+        camera.fovy = 1.0;
+        camera.frame(vec3(2.0, -1.0, 0.0), vec3(6.0, 1.0, 0.0));
+
+        assert_eq!(camera.target, vec3(4.0, 0.0, 0.0));
+        let offset = camera.position - camera.target;
+        assert!(offset.normalize().abs_diff_eq(vec3(0.0, 0.0, 1.0), 1e-6));
+        let radius = vec3(4.0, 2.0, 0.0).length() / 2.0;
+        assert!((offset.length() - framing_distance(radius, 1.0)).abs() < 1e-4);
+    }
+}
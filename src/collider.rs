@@ -0,0 +1,345 @@
+use crate::simulation::Mass;
+use crate::vec3::{Scalar, Vec3};
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Collider {
+    Sphere {
+        center: Vec3,
+        radius: Scalar,
+        friction: Scalar,
+        restitution: Scalar,
+        mask: u32,
+    },
+    Plane {
+        point: Vec3,
+        normal: Vec3,
+        friction: Scalar,
+        restitution: Scalar,
+        mask: u32,
+    },
+    Capsule {
+        a: Vec3,
+        b: Vec3,
+        radius: Scalar,
+        friction: Scalar,
+        restitution: Scalar,
+        mask: u32,
+    },
+    Aabb {
+        min: Vec3,
+        max: Vec3,
+        friction: Scalar,
+        restitution: Scalar,
+        mask: u32,
+    },
+}
+
+impl Collider {
+    pub fn resolve(&self, mass: &mut Mass, dt: Scalar) -> bool {
+        self.resolve_moving(mass, Vec3::zero(), dt)
+    }
+
+    // Friction and restitution act on the mass velocity relative to the
+    // collider, so a moving collider carries masses along with it
+    pub fn resolve_moving(&self, mass: &mut Mass, collider_velocity: Vec3, dt: Scalar) -> bool {
+        let Some((surface_point, normal)) = self.contact(mass.position) else {
+            return false;
+        };
+
+        mass.position = surface_point;
+
+        let relative_velocity = mass.velocity - collider_velocity;
+        let normal_velocity = relative_velocity.dot(&normal);
+        if normal_velocity < 0.0 {
+            let mut tangent_velocity = relative_velocity - normal_velocity * normal;
+
+            // Coulomb friction: the tangential speed lost is proportional to
+            // the normal speed that was just removed
+            let tangent_speed = tangent_velocity.length();
+            if tangent_speed > 0.0 {
+                let slowdown = self.friction() * -normal_velocity / tangent_speed;
+                tangent_velocity *= (1.0 - slowdown).max(0.0);
+            }
+            mass.velocity = collider_velocity + tangent_velocity
+                - self.restitution() * normal_velocity * normal;
+        }
+        mass.previous_position = mass.position - mass.velocity * dt;
+        true
+    }
+
+    pub fn translate(&mut self, offset: Vec3) {
+        match self {
+            Collider::Sphere { center, .. } => *center += offset,
+            Collider::Plane { point, .. } => *point += offset,
+            Collider::Capsule { a, b, .. } => {
+                *a += offset;
+                *b += offset;
+            }
+            Collider::Aabb { min, max, .. } => {
+                *min += offset;
+                *max += offset;
+            }
+        }
+    }
+
+    pub fn friction(&self) -> Scalar {
+        match self {
+            Collider::Sphere { friction, .. }
+            | Collider::Plane { friction, .. }
+            | Collider::Capsule { friction, .. }
+            | Collider::Aabb { friction, .. } => *friction,
+        }
+    }
+
+    pub fn restitution(&self) -> Scalar {
+        match self {
+            Collider::Sphere { restitution, .. }
+            | Collider::Plane { restitution, .. }
+            | Collider::Capsule { restitution, .. }
+            | Collider::Aabb { restitution, .. } => *restitution,
+        }
+    }
+
+    // Collision layers: only cloths sharing a bit with this mask touch the collider
+    pub fn mask(&self) -> u32 {
+        match self {
+            Collider::Sphere { mask, .. }
+            | Collider::Plane { mask, .. }
+            | Collider::Capsule { mask, .. }
+            | Collider::Aabb { mask, .. } => *mask,
+        }
+    }
+
+    fn contact(&self, position: Vec3) -> Option<(Vec3, Vec3)> {
+        match self {
+            Collider::Sphere { center, radius, .. } => sphere_contact(position, *center, *radius),
+            Collider::Plane { point, normal, .. } => {
+                let normal = normal.normalize();
+                let depth = (position - *point).dot(&normal);
+                if depth >= 0.0 {
+                    return None;
+                }
+
+                Some((position - depth * normal, normal))
+            }
+            Collider::Capsule { a, b, radius, .. } => {
+                sphere_contact(position, position.closest_point_on_segment(a, b), *radius)
+            }
+            Collider::Aabb { min, max, .. } => aabb_contact(position, *min, *max),
+        }
+    }
+}
+
+// Pushes out through the nearest face, which is also the nearest surface point
+// for anything inside the box. A point is inside only if every face depth is
+// positive, so the smallest depth decides both.
+fn aabb_contact(position: Vec3, min: Vec3, max: Vec3) -> Option<(Vec3, Vec3)> {
+    let faces = [
+        (position.x - min.x, Vec3::new(-1.0, 0.0, 0.0)),
+        (max.x - position.x, Vec3::new(1.0, 0.0, 0.0)),
+        (position.y - min.y, Vec3::new(0.0, -1.0, 0.0)),
+        (max.y - position.y, Vec3::new(0.0, 1.0, 0.0)),
+        (position.z - min.z, Vec3::new(0.0, 0.0, -1.0)),
+        (max.z - position.z, Vec3::new(0.0, 0.0, 1.0)),
+    ];
+    let (depth, normal) = faces
+        .into_iter()
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .unwrap();
+    if depth <= 0.0 {
+        return None;
+    }
+
+    Some((position + normal * depth, normal))
+}
+
+fn sphere_contact(position: Vec3, center: Vec3, radius: Scalar) -> Option<(Vec3, Vec3)> {
+    let offset = position - center;
+    let distance = offset.length();
+    if distance >= radius {
+        return None;
+    }
+
+    let normal = if distance > 0.0 {
+        offset / distance
+    } else {
+        Vec3::new(0.0, 1.0, 0.0)
+    };
+    Some((center + normal * radius, normal))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{Cloth, PinPattern};
+
+    fn sphere(center: Vec3, radius: Scalar) -> Collider {
+        Collider::Sphere {
+            center,
+            radius,
+            friction: 0.0,
+            restitution: 0.0,
+            mask: u32::MAX,
+        }
+    }
+
+    #[test]
+    fn sphere_pushes_mass_to_surface_and_stops_inward_motion() {
+        let collider = sphere(Vec3::zero(), 1.0);
+        let mut mass = Mass::new(Vec3::new(0.0, 0.5, 0.0), 1.0, false);
+        mass.velocity = Vec3::new(1.0, -2.0, 0.0);
+
+        collider.resolve(&mut mass, 0.01);
+        assert!(mass.position.approx_eq(&Vec3::new(0.0, 1.0, 0.0), 1e-6));
+        assert!(mass.velocity.approx_eq(&Vec3::new(1.0, 0.0, 0.0), 1e-6));
+    }
+
+    #[test]
+    fn cloth_dropped_on_a_sphere_stays_outside_it() {
+        let mut cloth = Cloth::new(10, 10, 0.4, 200.0);
+        cloth.translate(Vec3::new(-1.8, 1.5, -1.8));
+        cloth.add_collider(sphere(Vec3::zero(), 1.0));
+        for _ in 0..300 {
+            cloth.update(0.01, Vec3::zero(), 0.0);
+        }
+
+        for mass in &cloth.masses {
+            assert!(mass.position.length() >= 1.0 - 1e-4);
+        }
+    }
+
+    #[test]
+    fn cloth_released_above_the_ground_rests_on_it() {
+        let mut cloth = Cloth::new(6, 6, 0.5, 100.0);
+        cloth.translate(Vec3::new(0.0, 2.0, 0.0));
+        cloth.set_ground(0.0);
+        for _ in 0..500 {
+            cloth.update(0.01, Vec3::zero(), 0.0);
+        }
+
+        for mass in &cloth.masses {
+            assert!(mass.position.y >= -1e-4);
+        }
+        assert!(cloth.masses.iter().any(|mass| mass.position.y < 0.01));
+    }
+
+    #[test]
+    fn pinned_corner_stays_above_the_ground() {
+        let mut cloth = Cloth::new(6, 6, 0.5, 100.0);
+        cloth.translate(Vec3::new(0.0, 2.0, 0.0));
+        cloth.apply_pin_pattern(PinPattern::TopCorners);
+        cloth.set_ground(0.0);
+        for _ in 0..500 {
+            cloth.update(0.01, Vec3::zero(), 0.0);
+        }
+
+        let corner = &cloth.masses[0];
+        assert!(corner.is_pinned());
+        assert_eq!(corner.position, Vec3::new(0.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn cloth_dropped_over_a_capsule_stays_outside_it() {
+        let (a, b) = (Vec3::new(-2.0, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0));
+        let mut cloth = Cloth::new(10, 10, 0.4, 200.0);
+        cloth.translate(Vec3::new(-1.8, 1.0, -1.8));
+        cloth.add_collider(Collider::Capsule {
+            a,
+            b,
+            radius: 0.5,
+            friction: 0.0,
+            restitution: 0.0,
+            mask: u32::MAX,
+        });
+        for _ in 0..300 {
+            cloth.update(0.01, Vec3::zero(), 0.0);
+        }
+
+        for mass in &cloth.masses {
+            assert!(mass.position.distance_to_segment(&a, &b) >= 0.5 - 1e-4);
+        }
+    }
+
+    #[test]
+    fn friction_slows_sliding_down_a_tilted_plane() {
+        let slide = |friction: Scalar| {
+            let tilt: Scalar = 0.35;
+            let mut cloth = Cloth::new(3, 3, 0.5, 100.0);
+            cloth.translate(Vec3::new(0.0, 0.2, 0.0));
+            cloth.add_collider(Collider::Plane {
+                point: Vec3::zero(),
+                normal: Vec3::new(tilt.sin(), tilt.cos(), 0.0),
+                friction,
+                restitution: 0.0,
+                mask: u32::MAX,
+            });
+            for _ in 0..200 {
+                cloth.update(0.01, Vec3::zero(), 0.0);
+            }
+            cloth.center().x
+        };
+
+        let (smooth, rough) = (slide(0.0), slide(0.8));
+        assert!(rough < smooth - 0.5, "rough plane slid to {rough}, smooth to {smooth}");
+    }
+
+    #[test]
+    fn cloth_dropped_on_a_box_stays_out_of_it() {
+        let (min, max) = (Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 0.0, 1.0));
+        let mut cloth = Cloth::new(10, 10, 0.4, 200.0);
+        cloth.translate(Vec3::new(-1.8, 1.0, -1.8));
+        cloth.add_collider(Collider::Aabb {
+            min,
+            max,
+            friction: 0.0,
+            restitution: 0.0,
+            mask: u32::MAX,
+        });
+        for _ in 0..300 {
+            cloth.update(0.01, Vec3::zero(), 0.0);
+        }
+
+        let inside = |p: Vec3| {
+            let eps = 1e-4;
+            (min.x + eps..max.x - eps).contains(&p.x)
+                && (min.y + eps..max.y - eps).contains(&p.y)
+                && (min.z + eps..max.z - eps).contains(&p.z)
+        };
+        assert!(cloth.masses.iter().all(|mass| !inside(mass.position)));
+        assert!(cloth.masses.iter().any(|mass| mass.position.y.abs() < 1e-3));
+    }
+
+    #[test]
+    fn restitution_sets_the_rebound_height() {
+        let rebound = |restitution: Scalar| {
+            let mut cloth = Cloth::new(1, 1, 1.0, 100.0);
+            cloth.set_damping(0.0);
+            cloth.translate(Vec3::new(0.0, 1.0, 0.0));
+            cloth.add_collider(Collider::Plane {
+                point: Vec3::zero(),
+                normal: Vec3::new(0.0, 1.0, 0.0),
+                friction: 0.0,
+                restitution,
+                mask: u32::MAX,
+            });
+
+            // Fall until the first bounce, then track the peak of the rebound
+            let mut bounced = false;
+            let mut peak: Scalar = 0.0;
+            for _ in 0..1500 {
+                cloth.update(0.001, Vec3::zero(), 0.0);
+                let mass = &cloth.masses[0];
+                bounced |= mass.velocity.y > 0.0;
+                if bounced {
+                    peak = peak.max(mass.position.y);
+                }
+            }
+            (peak, cloth.masses[0].position.y)
+        };
+
+        let (bouncy, _) = rebound(0.8);
+        assert!((bouncy - 0.64).abs() < 0.03, "rebounded to {bouncy}");
+        let (peak, resting) = rebound(0.0);
+        assert!(peak < 1e-3 && resting.abs() < 1e-3);
+    }
+}
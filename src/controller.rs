@@ -0,0 +1,83 @@
+use cloth_model::vec3::Scalar;
+
+pub const WIND_STEP: Scalar = 1.0;
+const MAX_WIND_SPEED: Scalar = 50.0;
+
+pub fn adjust_wind_speed(wind_speed: Scalar, delta: Scalar) -> Scalar {
+    (wind_speed + delta).clamp(0.0, MAX_WIND_SPEED)
+}
+
+#[derive(Default)]
+pub struct SimController {
+    paused: bool,
+    step_requested: bool,
+}
+
+impl SimController {
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.step_requested = false;
+    }
+
+    pub fn request_step(&mut self) {
+        if self.paused {
+            self.step_requested = true;
+        }
+    }
+
+    pub fn should_step(&mut self) -> bool {
+        if !self.paused {
+            return true;
+        }
+
+        std::mem::take(&mut self.step_requested)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Counts the updates a frame loop would run, the way main.rs drives the cloth
+    fn run_frames(controller: &mut SimController, frames: usize) -> usize {
+        (0..frames).filter(|_| controller.should_step()).count()
+    }
+
+    #[test]
+    fn paused_controller_skips_updates() {
+        let mut controller = SimController::default();
+        assert_eq!(run_frames(&mut controller, 5), 5);
+
+        controller.toggle_pause();
+        assert!(controller.is_paused());
+        assert_eq!(run_frames(&mut controller, 5), 0);
+    }
+
+    #[test]
+    fn single_step_runs_exactly_one_update_while_paused() {
+        let mut controller = SimController::default();
+        controller.toggle_pause();
+        controller.request_step();
+        assert_eq!(run_frames(&mut controller, 5), 1);
+
+        // Step requests only count while paused
+        controller.toggle_pause();
+        controller.request_step();
+        controller.toggle_pause();
+        assert_eq!(run_frames(&mut controller, 5), 0);
+    }
+
+    #[test]
+    fn wind_speed_steps_and_clamps_to_its_range() {
+        assert_eq!(adjust_wind_speed(10.0, WIND_STEP), 11.0);
+        assert_eq!(adjust_wind_speed(10.0, -WIND_STEP), 9.0);
+        assert_eq!(adjust_wind_speed(0.5, -WIND_STEP), 0.0);
+        assert_eq!(adjust_wind_speed(0.0, -WIND_STEP), 0.0);
+        assert_eq!(adjust_wind_speed(MAX_WIND_SPEED - 0.5, WIND_STEP), MAX_WIND_SPEED);
+        assert_eq!(adjust_wind_speed(MAX_WIND_SPEED, WIND_STEP), MAX_WIND_SPEED);
+    }
+}
@@ -0,0 +1,14 @@
+use crate::vec3::{Scalar, Vec3};
+
+pub const DEFAULT_DT: Scalar = 0.02;
+pub const DEFAULT_COLS: usize = 20;
+pub const DEFAULT_ROWS: usize = 20;
+pub const DEFAULT_SPACING: Scalar = 1.0;
+pub const DEFAULT_STIFFNESS: Scalar = 100.0;
+pub const DEFAULT_MASS: Scalar = 0.5;
+pub const DEFAULT_DAMPING: Scalar = 0.3;
+pub const DEFAULT_GRAVITY: Vec3 = Vec3 {
+    x: 0.0,
+    y: -9.81,
+    z: 0.0,
+};
@@ -0,0 +1,8 @@
+pub mod builder;
+pub mod collider;
+pub mod defaults;
+pub mod noise;
+pub mod simulation;
+pub mod spatial_hash;
+pub mod stepper;
+pub mod vec3;
@@ -1,57 +1,85 @@
-mod vec3;
-mod simulation;
 mod camera;
+mod controller;
+mod render;
 
+use cloth_model::builder::ClothBuilder;
+use cloth_model::defaults::{
+    DEFAULT_COLS, DEFAULT_DAMPING, DEFAULT_DT, DEFAULT_ROWS, DEFAULT_SPACING, DEFAULT_STIFFNESS,
+};
+use cloth_model::simulation::PinPattern;
+use cloth_model::stepper::FixedStepper;
+use cloth_model::vec3::Vec3;
 use macroquad::prelude::*;
-use vec3::Vec3;
-use simulation::Cloth;
 use camera::Camera;
+use controller::{adjust_wind_speed, SimController, WIND_STEP};
+use render::RenderMode;
 
 #[macroquad::main("Cloth Simulation 3D")]
 async fn main() {
-    let mass_value = 0.5;
-    let dt = 0.02;
-    let damping = 0.3;
+    render::configure_draw_calls();
+
+    let dt = DEFAULT_DT;
     let wind = Vec3::new(1.0, 0.0, 1.0);
-    let wind_speed = 10.0;
+    let mut wind_speed = 10.0;
+
+    let cols = DEFAULT_COLS;
+    let rows = DEFAULT_ROWS;
 
-    let mut cloth = Cloth::new(20, 20, 1.0, 100.0);
+    let mut cloth = ClothBuilder::new()
+        .dimensions(cols, rows)
+        .spacing(DEFAULT_SPACING)
+        .stiffness(DEFAULT_STIFFNESS)
+        .damping(DEFAULT_DAMPING)
+        .pin_pattern(PinPattern::TopCorners)
+        .build();
+    let mut stepper = FixedStepper::new(dt, 10);
     let mut camera = Camera::new(vec3(30.0, 30.0, 30.0), vec3(0.0, 0.0, 0.0));
+    let mut render_mode = RenderMode::Wireframe;
+    let mut show_normals = false;
+    let mut controller = SimController::default();
 
     loop {
-        cloth.update(dt, damping, mass_value, wind, wind_speed);
+        if is_key_pressed(KeyCode::R) {
+            cloth.reset();
+        }
+        if is_key_pressed(KeyCode::F) {
+            let (min, max) = cloth.bounds();
+            camera.frame(min.into(), max.into());
+        }
+        if is_key_pressed(KeyCode::Tab) {
+            render_mode = render_mode.toggle();
+        }
+        if is_key_pressed(KeyCode::N) {
+            show_normals = !show_normals;
+        }
+        if is_key_pressed(KeyCode::Space) {
+            controller.toggle_pause();
+        }
+        if is_key_pressed(KeyCode::Period) {
+            controller.request_step();
+        }
+        if is_key_pressed(KeyCode::RightBracket) {
+            wind_speed = adjust_wind_speed(wind_speed, WIND_STEP);
+        }
+        if is_key_pressed(KeyCode::LeftBracket) {
+            wind_speed = adjust_wind_speed(wind_speed, -WIND_STEP);
+        }
+        if controller.should_step() {
+            let steps = if controller.is_paused() {
+                1
+            } else {
+                stepper.advance(get_frame_time())
+            };
+            for _ in 0..steps {
+                cloth.update(dt, wind, wind_speed);
+            }
+        }
         camera.update();
         clear_background(BLACK);
         camera.set_active();
-        draw_scene(&cloth);
+        render::draw_scene(&cloth, render_mode, show_normals);
         set_default_camera();
+        render::draw_hud(&cloth, wind_speed);
         next_frame().await;
     }
 }
-
-fn draw_scene(cloth: &Cloth) {
-    for spring in &cloth.structural_springs {
-        let a = cloth.masses[spring.a].position;
-        let b = cloth.masses[spring.b].position;
-        draw_line_3d(a.into(), b.into(), WHITE);
-    }
-
-    for mass in &cloth.masses {
-        draw_sphere(mass.position.into(), 0.1, None, RED);
-        draw_line_3d(mass.position.into(), (mass.position + mass.normal).into(), YELLOW);
-    }
-
-    let x_vec = Vec3::new(10.0, 0.0, 0.0);
-    let y_vec = Vec3::new(0.0, 10.0, 0.0);
-    let z_vec = Vec3::new(0.01, 0.0, 10.0);
-
-    draw_line_3d(Vec3::zero().into(), x_vec.into(), RED);
-    draw_line_3d(Vec3::zero().into(), y_vec.into(), GREEN);
-    draw_line_3d(Vec3::zero().into(), z_vec.into(), BLUE);
-}
-
-impl Into<macroquad::prelude::Vec3> for Vec3 {
-    fn into(self) -> macroquad::prelude::Vec3 {
-        macroquad::prelude::Vec3::new(self.x as f32, self.y as f32, self.z as f32)
-    }
-}
\ No newline at end of file
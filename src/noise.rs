@@ -0,0 +1,33 @@
+use crate::vec3::{Scalar, Vec3};
+
+// Smoothly interpolated random values on an integer lattice, in [-1, 1]. The
+// same point and seed always give the same value.
+pub fn value_noise(p: Vec3, seed: u32) -> Scalar {
+    let (x0, y0, z0) = (p.x.floor(), p.y.floor(), p.z.floor());
+    let (tx, ty, tz) = (smoothstep(p.x - x0), smoothstep(p.y - y0), smoothstep(p.z - z0));
+    let (ix, iy, iz) = (x0 as i32, y0 as i32, z0 as i32);
+
+    let corner = |dx: i32, dy: i32, dz: i32| lattice(ix + dx, iy + dy, iz + dz, seed);
+    let lerp = |a: Scalar, b: Scalar, t: Scalar| a + (b - a) * t;
+
+    let x00 = lerp(corner(0, 0, 0), corner(1, 0, 0), tx);
+    let x10 = lerp(corner(0, 1, 0), corner(1, 1, 0), tx);
+    let x01 = lerp(corner(0, 0, 1), corner(1, 0, 1), tx);
+    let x11 = lerp(corner(0, 1, 1), corner(1, 1, 1), tx);
+    lerp(lerp(x00, x10, ty), lerp(x01, x11, ty), tz)
+}
+
+fn smoothstep(t: Scalar) -> Scalar {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn lattice(x: i32, y: i32, z: i32, seed: u32) -> Scalar {
+    let mut h = (x as u32)
+        .wrapping_mul(0x8da6_b343)
+        .wrapping_add((y as u32).wrapping_mul(0xd816_3841))
+        .wrapping_add((z as u32).wrapping_mul(0xcb1a_b31f))
+        .wrapping_add(seed.wrapping_mul(0x9e37_79b9));
+    h = (h ^ (h >> 13)).wrapping_mul(0x5bd1_e995);
+    h ^= h >> 15;
+    h as Scalar / u32::MAX as Scalar * 2.0 - 1.0
+}
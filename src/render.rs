@@ -0,0 +1,163 @@
+use cloth_model::simulation::Cloth;
+use cloth_model::vec3::{Scalar, Vec3};
+use macroquad::models::{Mesh, Vertex};
+use macroquad::prelude::*;
+
+const CLOTH_COLOR: Color = Color::new(0.8, 0.2, 0.2, 1.0);
+const AMBIENT: f32 = 0.2;
+// Per draw call buffer sizes, set on the context by configure_draw_calls. Past
+// either limit QuadGl::geometry warns and drops the rest of the mesh.
+const DRAW_CALL_MAX_VERTICES: usize = 10_000;
+const DRAW_CALL_MAX_INDICES: usize = 5_000;
+// Whole triangles strictly below both limits
+const MAX_MESH_VERTICES: usize = {
+    let limit = if DRAW_CALL_MAX_VERTICES < DRAW_CALL_MAX_INDICES {
+        DRAW_CALL_MAX_VERTICES
+    } else {
+        DRAW_CALL_MAX_INDICES
+    };
+    (limit - 1) / 3 * 3
+};
+// Strain at which the stretch colors are fully saturated
+const FULL_STRAIN: f32 = 0.25;
+const NORMAL_LENGTH: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderMode {
+    Wireframe,
+    Solid,
+    Outline,
+    Stretch,
+}
+
+impl RenderMode {
+    pub fn toggle(self) -> Self {
+        match self {
+            RenderMode::Wireframe => RenderMode::Solid,
+            RenderMode::Solid => RenderMode::Outline,
+            RenderMode::Outline => RenderMode::Stretch,
+            RenderMode::Stretch => RenderMode::Wireframe,
+        }
+    }
+}
+
+pub fn configure_draw_calls() {
+    gl_set_drawcall_buffer_capacity(DRAW_CALL_MAX_VERTICES, DRAW_CALL_MAX_INDICES);
+}
+
+pub fn draw_scene(cloth: &Cloth, mode: RenderMode, show_normals: bool) {
+    match mode {
+        RenderMode::Wireframe => draw_wireframe(cloth),
+        RenderMode::Solid => draw_solid(cloth),
+        RenderMode::Outline => draw_outline(cloth),
+        RenderMode::Stretch => draw_stretch(cloth),
+    }
+
+    if show_normals {
+        draw_normals(cloth);
+    }
+
+    draw_axes();
+}
+
+fn draw_wireframe(cloth: &Cloth) {
+    for spring in &cloth.structural_springs {
+        let a = cloth.masses[spring.a].position;
+        let b = cloth.masses[spring.b].position;
+        draw_line_3d(a.into(), b.into(), WHITE);
+    }
+
+    for mass in &cloth.masses {
+        draw_sphere(mass.position.into(), 0.1, None, RED);
+    }
+}
+
+fn draw_normals(cloth: &Cloth) {
+    for (mass, normal) in cloth.masses.iter().zip(cloth.vertex_normals()) {
+        let tip = mass.position + normal * NORMAL_LENGTH;
+        draw_line_3d(mass.position.into(), tip.into(), YELLOW);
+    }
+}
+
+fn draw_outline(cloth: &Cloth) {
+    for (a, b) in cloth.boundary_springs() {
+        let a = cloth.masses[a].position;
+        let b = cloth.masses[b].position;
+        draw_line_3d(a.into(), b.into(), WHITE);
+    }
+}
+
+// Blue for compressed springs, white at rest length, red for stretched
+fn draw_stretch(cloth: &Cloth) {
+    for spring in &cloth.structural_springs {
+        let a = cloth.masses[spring.a].position;
+        let b = cloth.masses[spring.b].position;
+        let t = (spring.strain(&cloth.masses) / FULL_STRAIN).clamp(-1.0, 1.0);
+        let color = if t < 0.0 {
+            lerp_color(WHITE, BLUE, -t)
+        } else {
+            lerp_color(WHITE, RED, t)
+        };
+        draw_line_3d(a.into(), b.into(), color);
+    }
+}
+
+fn lerp_color(from: Color, to: Color, t: f32) -> Color {
+    Color::from_vec(from.to_vec().lerp(to.to_vec(), t))
+}
+
+fn draw_solid(cloth: &Cloth) {
+    let light = Vec3::new(0.3, 1.0, 0.5).normalize();
+    let mut vertices = Vec::new();
+
+    for triangle in cloth.triangles() {
+        let [a, b, c] = triangle.map(|index| cloth.masses[index].position);
+        let normal = (b - a).cross(&(c - a)).normalize();
+
+        // Shade both faces the same so the cloth is lit from either side
+        let intensity = AMBIENT + (1.0 - AMBIENT) * normal.dot(&light).abs();
+        let color = Color::new(
+            CLOTH_COLOR.r * intensity,
+            CLOTH_COLOR.g * intensity,
+            CLOTH_COLOR.b * intensity,
+            1.0,
+        );
+
+        for position in [a, b, c] {
+            vertices.push(Vertex {
+                position: position.into(),
+                uv: vec2(0.0, 0.0),
+                color,
+            });
+        }
+    }
+
+    for chunk in vertices.chunks(MAX_MESH_VERTICES) {
+        draw_mesh(&Mesh {
+            vertices: chunk.to_vec(),
+            indices: (0..chunk.len() as u16).collect(),
+            texture: None,
+        });
+    }
+}
+
+pub fn draw_hud(cloth: &Cloth, wind_speed: Scalar) {
+    let lines = [
+        format!("fps: {}", get_fps()),
+        format!("steps: {}", cloth.steps()),
+        format!("wind: {wind_speed:.1}  ([ / ] to change)"),
+    ];
+    for (i, line) in lines.iter().enumerate() {
+        draw_text(line, 10.0, 20.0 + 20.0 * i as f32, 20.0, WHITE);
+    }
+}
+
+fn draw_axes() {
+    let x_vec = Vec3::new(10.0, 0.0, 0.0);
+    let y_vec = Vec3::new(0.0, 10.0, 0.0);
+    let z_vec = Vec3::new(0.01, 0.0, 10.0);
+
+    draw_line_3d(Vec3::zero().into(), x_vec.into(), RED);
+    draw_line_3d(Vec3::zero().into(), y_vec.into(), GREEN);
+    draw_line_3d(Vec3::zero().into(), z_vec.into(), BLUE);
+}
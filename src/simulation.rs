@@ -1,273 +1,3827 @@
-use crate::vec3::Vec3;
+use crate::collider::Collider;
+use crate::noise::value_noise;
+use crate::defaults::{
+    DEFAULT_COLS, DEFAULT_DAMPING, DEFAULT_GRAVITY, DEFAULT_MASS, DEFAULT_ROWS, DEFAULT_SPACING,
+    DEFAULT_STIFFNESS,
+};
+use crate::spatial_hash::SpatialHash;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashSet};
+use std::f32::consts::TAU;
+use std::fmt::Write;
+use crate::vec3::{centroid, Scalar, Vec3};
 
-const GRAVITY: Vec3 = Vec3{x: 0.0, y: -9.81, z:0.0};
+// Upper bound on stretch clamp passes per substep
+const MAX_STRETCH_PASSES: usize = 50;
+// Strain past which is_stable treats the cloth as blowing up
+const MAX_STABLE_STRAIN: Scalar = 1.0;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Integrator {
+    Euler,
+    Verlet,
+    Rk4,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PinPattern {
+    None,
+    TopCorners,
+    TopEdge,
+    LeftEdge,
+    AllCorners,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+// Horizontal lays the grid in the XZ plane, Vertical hangs it down the XY plane
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpringKind {
+    Structural,
+    Shear,
+    Bend,
+}
+
+// DistanceSpring resists bending with the long bend springs, Dihedral with the
+// angle between neighbouring triangles, which leaves in-plane stretch alone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BendModel {
+    DistanceSpring,
+    Dihedral,
+}
+
+// OneSided only pushes on the face the wind hits, TwoSided lets either face catch it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WindModel {
+    OneSided,
+    TwoSided,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpringStiffness {
+    pub structural: Scalar,
+    pub shear: Scalar,
+    pub bend: Scalar,
+}
+
+impl SpringStiffness {
+    pub fn uniform(stiffness: Scalar) -> Self {
+        SpringStiffness {
+            structural: stiffness,
+            shear: stiffness,
+            bend: stiffness,
+        }
+    }
+}
+
+impl From<Scalar> for SpringStiffness {
+    fn from(stiffness: Scalar) -> Self {
+        SpringStiffness::uniform(stiffness)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StepReport {
+    pub torn_springs: Vec<(usize, usize)>,
+    pub collisions: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StrainStats {
+    pub max: Scalar,
+    pub mean: Scalar,
+    pub max_index: usize,
+}
+
+// Returned by Cloth::stitch when the two edges hold different numbers of masses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StitchError {
+    pub self_edge_len: usize,
+    pub other_edge_len: usize,
+}
+
+impl std::fmt::Display for StitchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "stitched edges must have the same length, got {} and {}",
+            self.self_edge_len, self.other_edge_len
+        )
+    }
+}
+
+impl std::error::Error for StitchError {}
+
+// Sum of force magnitudes over the free masses, one total per force category
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ForceReport {
+    pub spring: Scalar,
+    pub gravity: Scalar,
+    pub damping: Scalar,
+    pub wind: Scalar,
+    pub pressure: Scalar,
+    pub external: Scalar,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Turbulence {
+    base: Vec3,
+    strength: Scalar,
+    scale: Scalar,
+}
+
+impl Turbulence {
+    // Each component reads its own noise channel. The field drifts through time
+    // so gusts move across the cloth instead of pulsing in place.
+    fn sample(&self, position: Vec3, time: Scalar) -> Vec3 {
+        let p = position / self.scale + Vec3::new(time, 0.5 * time, 0.25 * time);
+        let gust = Vec3::new(value_noise(p, 0), value_noise(p, 1), value_noise(p, 2));
+        self.base + gust * self.strength
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Grab {
+    index: usize,
+    target: Vec3,
+    was_pinned: bool,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Anchor {
+    index: usize,
+    position: Vec3,
+    was_pinned: bool,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Cloth {
     pub masses: Vec<Mass>,
     pub structural_springs: Vec<Spring>,
     shear_springs: Vec<Spring>,
     bend_springs: Vec<Spring>,
+    // Groups of springs indexed in all_springs order, no two in a group sharing
+    // a mass. Cleared whenever springs are added or removed.
+    spring_colors: Vec<Vec<usize>>,
+    bend_model: BendModel,
+    bend_stiffness: Scalar,
+    enable_shear: bool,
+    enable_bend: bool,
     rows: usize,
     cols: usize,
+    wrap_cols: bool,
+    gravity: Vec3,
+    colliders: Vec<Collider>,
+    // Collider motion queued for the next update, as (collider, offset)
+    collider_sweeps: Vec<(usize, Vec3)>,
+    // Per-collider velocity while an update sweeps the colliders
+    #[cfg_attr(feature = "serde", serde(skip))]
+    collider_velocities: Vec<Vec3>,
+    collision_mask: u32,
+    rest_positions: Vec<Vec3>,
+    // Cleared once masses are removed, after which every triangle is listed
+    // explicitly in extra_triangles and row/column lookups find nothing
+    grid_triangles: bool,
+    // Triangles outside the grid, added when panels are stitched on
+    extra_triangles: Vec<[usize; 3]>,
+    solver_iterations: usize,
+    substeps: usize,
+    damping: Scalar,
+    air_drag: Scalar,
+    integrator: Integrator,
+    wind_model: WindModel,
+    self_collision_radius: Option<Scalar>,
+    max_stretch: Option<Scalar>,
+    max_velocity: Option<Scalar>,
+    pressure: Option<(Scalar, Scalar)>,
+    turbulence: Option<Turbulence>,
+    adaptive: Option<(Scalar, Scalar)>,
+    subdivisions: u64,
+    sleep: Option<(Scalar, u32)>,
+    still_steps: u32,
+    // The wind the cloth fell asleep under, so a different wind can wake it
+    asleep: Option<(Vec3, Scalar)>,
+    steps: u64,
+    time: Scalar,
+    instability_count: u64,
+    grab: Option<Grab>,
+    anchors: Vec<Anchor>,
+    trace_index: Option<usize>,
+    trace: Vec<(Scalar, Vec3)>,
+    force_report: Option<ForceReport>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    wind_fn: Option<Box<dyn FnMut(Scalar) -> Vec3>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pin_animation: Option<Box<dyn Fn(Scalar) -> Vec3>>,
 }
 
 impl Cloth {
-    pub fn new(cols: usize, rows: usize, spacing: f32, stiffness: f32) -> Self {
-        let mut cloth = Cloth {
+    pub fn new(
+        cols: usize,
+        rows: usize,
+        spacing: Scalar,
+        stiffness: impl Into<SpringStiffness>,
+    ) -> Self {
+        Cloth::new_rect(cols, rows, spacing, spacing, stiffness)
+    }
+
+    pub fn new_rect(
+        cols: usize,
+        rows: usize,
+        spacing_x: Scalar,
+        spacing_z: Scalar,
+        stiffness: impl Into<SpringStiffness>,
+    ) -> Self {
+        let mut cloth = Cloth::empty(cols, rows, false);
+        cloth.init_masses(|i, j| Vec3::new(j as Scalar * spacing_x, 0.0, i as Scalar * spacing_z));
+        cloth.init_springs(stiffness.into());
+        cloth
+    }
+
+    pub fn new_oriented(
+        cols: usize,
+        rows: usize,
+        spacing: Scalar,
+        stiffness: impl Into<SpringStiffness>,
+        orientation: Orientation,
+    ) -> Self {
+        let mut cloth = Cloth::empty(cols, rows, false);
+        cloth.init_masses(|i, j| {
+            let (x, along_rows) = (j as Scalar * spacing, i as Scalar * spacing);
+            match orientation {
+                Orientation::Horizontal => Vec3::new(x, 0.0, along_rows),
+                Orientation::Vertical => Vec3::new(x, -along_rows, 0.0),
+            }
+        });
+        cloth.init_springs(stiffness.into());
+        cloth
+    }
+
+    // Sizes the sheet by its physical extent, so cloths of different resolution
+    // cover the same area
+    pub fn with_resolution(
+        cols: usize,
+        rows: usize,
+        total_width: Scalar,
+        total_height: Scalar,
+        stiffness: impl Into<SpringStiffness>,
+    ) -> Self {
+        let spacing_x = total_width / cols.saturating_sub(1).max(1) as Scalar;
+        let spacing_z = total_height / rows.saturating_sub(1).max(1) as Scalar;
+        Cloth::new_rect(cols, rows, spacing_x, spacing_z, stiffness)
+    }
+
+    // Wraps the columns around a vertical cylinder of the given radius, with row 0
+    // at the top. The last column is connected back to the first across the seam.
+    pub fn new_cylinder(
+        cols: usize,
+        rows: usize,
+        radius: Scalar,
+        spacing: Scalar,
+        stiffness: impl Into<SpringStiffness>,
+    ) -> Self {
+        let mut cloth = Cloth::empty(cols, rows, true);
+        cloth.init_masses(|i, j| {
+            let angle = TAU * j as Scalar / cols as Scalar;
+            Vec3::from_spherical(radius, angle, 0.0) - Vec3::new(0.0, i as Scalar * spacing, 0.0)
+        });
+        cloth.init_springs(stiffness.into());
+        cloth
+    }
+
+    fn empty(cols: usize, rows: usize, wrap_cols: bool) -> Self {
+        Cloth {
             masses: Vec::new(),
             structural_springs: Vec::new(),
             shear_springs: Vec::new(),
             bend_springs: Vec::new(),
+            spring_colors: Vec::new(),
+            bend_model: BendModel::DistanceSpring,
+            bend_stiffness: 0.0,
+            enable_shear: true,
+            enable_bend: true,
             rows,
             cols,
-        };
-        cloth.init(spacing, stiffness);
-        cloth
+            wrap_cols,
+            gravity: DEFAULT_GRAVITY,
+            colliders: Vec::new(),
+            collider_sweeps: Vec::new(),
+            collider_velocities: Vec::new(),
+            collision_mask: u32::MAX,
+            rest_positions: Vec::new(),
+            grid_triangles: true,
+            extra_triangles: Vec::new(),
+            solver_iterations: 1,
+            substeps: 1,
+            damping: DEFAULT_DAMPING,
+            air_drag: 0.0,
+            integrator: Integrator::Euler,
+            wind_model: WindModel::TwoSided,
+            self_collision_radius: None,
+            max_stretch: None,
+            max_velocity: None,
+            pressure: None,
+            turbulence: None,
+            adaptive: None,
+            subdivisions: 0,
+            sleep: None,
+            still_steps: 0,
+            asleep: None,
+            steps: 0,
+            time: 0.0,
+            instability_count: 0,
+            grab: None,
+            anchors: Vec::new(),
+            trace_index: None,
+            trace: Vec::new(),
+            force_report: None,
+            wind_fn: None,
+            pin_animation: None,
+        }
     }
 
-    fn init(&mut self, spacing: f32, stiffness: f32) {
-        // Init masses
+    fn init_masses(&mut self, layout: impl Fn(usize, usize) -> Vec3) {
         for i in 0..self.rows {
             for j in 0..self.cols {
-                let mut new_point = Mass::new(
-                    Vec3::new(j as f32 * spacing, 0.0, i as f32 * spacing),
-                    false,
-                );
-                if i == 0 && j == 0 || i == 0 && j == self.cols - 1 {
-                    new_point.pinned = true;
-                }
-                self.masses.push(new_point);
+                self.masses.push(Mass::new(layout(i, j), DEFAULT_MASS, false));
             }
         }
+        self.rest_positions = self.masses.iter().map(|mass| mass.position).collect();
+    }
 
-        // Init springs
+    fn init_springs(&mut self, stiffness: SpringStiffness) {
+        self.bend_stiffness = stiffness.bend;
         for i in 0..self.rows {
             for j in 0..self.cols {
                 let index = i * self.cols + j;
 
                 // Structural springs
-                if j < self.cols - 1 {
-                    self.structural_springs
-                        .push(Spring::new(index, index + 1, spacing, stiffness));
+                for (d_row, d_col) in [(0, 1), (0, -1), (-1, 0), (1, 0)] {
+                    if let Some(other) = self.neighbor(i, j, d_row, d_col) {
+                        let spring = self.spring_between(index, other, stiffness.structural);
+                        self.structural_springs.push(spring);
+                    }
                 }
 
-                if j > 0 {
-                    self.structural_springs
-                        .push(Spring::new(index, index - 1, spacing, stiffness));
+                // Shear springs
+                for (d_row, d_col) in [(-1, -1), (-1, 1), (1, -1), (1, 1)] {
+                    if let Some(other) = self.neighbor(i, j, d_row, d_col) {
+                        let spring = self.spring_between(index, other, stiffness.shear);
+                        self.shear_springs.push(spring);
+                    }
                 }
 
-                if i > 0 {
-                    self.structural_springs.push(Spring::new(
-                        index,
-                        index - self.cols,
-                        spacing,
-                        stiffness,
-                    ));
+                // Bend springs
+                for (d_row, d_col) in [(-2, 0), (0, 2), (2, 0), (0, -2)] {
+                    if let Some(other) = self.neighbor(i, j, d_row, d_col) {
+                        let spring = self.spring_between(index, other, stiffness.bend);
+                        self.bend_springs.push(spring);
+                    }
                 }
+            }
+        }
+    }
 
-                if i < self.rows - 1 {
-                    self.structural_springs.push(Spring::new(
-                        index,
-                        index + self.cols,
-                        spacing,
-                        stiffness,
-                    ));
-                }
+    fn neighbor(&self, row: usize, col: usize, d_row: isize, d_col: isize) -> Option<usize> {
+        let row = row.checked_add_signed(d_row).filter(|&row| row < self.rows)?;
+        let neighbor_col = if self.wrap_cols {
+            (col as isize + d_col).rem_euclid(self.cols as isize) as usize
+        } else {
+            col.checked_add_signed(d_col).filter(|&col| col < self.cols)?
+        };
 
-                // Shear springs
-                let shear_rest_length = (2.0 * spacing * spacing).sqrt();
-                if j > 0 && i > 0 {
-                    self.shear_springs.push(Spring::new(
-                        index,
-                        index - self.cols - 1,
-                        shear_rest_length,
-                        stiffness,
-                    ));
-                }
+        let index = row * self.cols + neighbor_col;
+        // Narrow wrapped grids can fold a neighbor back onto the mass itself
+        (neighbor_col != col || d_col == 0).then_some(index)
+    }
 
-                if j < self.cols - 1 && i > 0 {
-                    self.shear_springs.push(Spring::new(
-                        index,
-                        index - self.cols + 1,
-                        shear_rest_length,
-                        stiffness,
-                    ));
-                }
+    fn spring_between(&self, a: usize, b: usize, stiffness: Scalar) -> Spring {
+        let rest_length = self.rest_positions[a].distance(&self.rest_positions[b]);
+        Spring::new(a, b, rest_length, stiffness)
+    }
 
-                if j > 0 && i < self.rows - 1 {
-                    self.shear_springs.push(Spring::new(
-                        index,
-                        index + self.cols - 1,
-                        shear_rest_length,
-                        stiffness,
-                    ));
-                }
+    pub fn reset(&mut self) {
+        for (mass, rest_position) in self.masses.iter_mut().zip(&self.rest_positions) {
+            mass.position = *rest_position;
+            mass.previous_position = *rest_position;
+            mass.velocity = Vec3::zero();
+            mass.acceleration = Vec3::zero();
+        }
+        self.steps = 0;
+        self.time = 0.0;
+        self.wake();
+    }
 
-                if j < self.cols - 1 && i < self.cols - 1 {
-                    self.shear_springs.push(Spring::new(
-                        index,
-                        index + self.cols + 1,
-                        shear_rest_length,
-                        stiffness,
-                    ));
-                }
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
 
-                // Bend springs
-                let bend_rest_length = spacing * 2.0;
-                if i > 1 {
-                    self.bend_springs.push(Spring::new(
-                        index,
-                        index - 2 * self.cols,
-                        bend_rest_length,
-                        stiffness,
-                    ));
-                }
+    pub fn time(&self) -> Scalar {
+        self.time
+    }
 
-                if j < self.cols - 2 {
-                    self.bend_springs.push(Spring::new(
-                        index,
-                        index + 2,
-                        bend_rest_length,
-                        stiffness,
-                    ));
-                }
+    pub fn subdivisions(&self) -> u64 {
+        self.subdivisions
+    }
 
-                if i < self.rows - 2 {
-                    self.bend_springs.push(Spring::new(
-                        index,
-                        index + 2 * self.cols,
-                        bend_rest_length,
-                        stiffness,
-                    ));
-                }
+    pub fn instability_count(&self) -> u64 {
+        self.instability_count
+    }
 
-                if j > 1 {
-                    self.bend_springs.push(Spring::new(
-                        index,
-                        index - 2,
-                        bend_rest_length,
-                        stiffness,
-                    ));
-                }
+    // Puts masses whose state became non-finite back at their last valid
+    // position (or their rest position if that is gone too) and stops them
+    pub fn sanitize(&mut self) {
+        for (mass, rest_position) in self.masses.iter_mut().zip(&self.rest_positions) {
+            if mass.position.is_finite() && mass.velocity.is_finite() {
+                continue;
             }
+
+            mass.position = if mass.previous_position.is_finite() {
+                mass.previous_position
+            } else {
+                *rest_position
+            };
+            mass.previous_position = mass.position;
+            mass.velocity = Vec3::zero();
+            mass.acceleration = Vec3::zero();
+            self.instability_count += 1;
         }
     }
 
-    pub fn calculate_normals(&mut self) {
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("cloth state is always serializable")
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_json(s: &str) -> Result<Cloth, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+
+    pub fn positions(&self) -> Vec<Vec3> {
+        self.masses.iter().map(|mass| mass.position).collect()
+    }
+
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        let first = self.masses.first().map_or(Vec3::zero(), |mass| mass.position);
+        self.masses.iter().fold((first, first), |(min, max), mass| {
+            (min.min(&mass.position), max.max(&mass.position))
+        })
+    }
+
+    pub fn center(&self) -> Vec3 {
+        let (min, max) = self.bounds();
+        (min + max) / 2.0
+    }
+
+    pub fn translate(&mut self, offset: Vec3) {
+        self.transform_points(|p| p + offset);
+        self.wake();
+    }
+
+    pub fn rotate(&mut self, axis: Vec3, angle: Scalar) {
+        let center = self.center();
+        self.transform_points(|p| center + (p - center).rotate_around(&axis, angle));
         for mass in &mut self.masses {
-            mass.normal = Vec3::zero();
+            mass.velocity = mass.velocity.rotate_around(&axis, angle);
         }
+        self.wake();
+    }
 
-        for i in 0..self.rows - 1 {
-            for j in 0..self.cols - 1 {
-                let idx = i * self.cols + j;
-                let right = idx + 1;
-                let down = idx + self.cols;
-                let down_right = idx + self.cols + 1;
+    pub fn scale(&mut self, factor: Scalar) {
+        let center = self.center();
+        self.transform_points(|p| center + (p - center) * factor);
+        for spring in self
+            .structural_springs
+            .iter_mut()
+            .chain(self.shear_springs.iter_mut())
+            .chain(self.bend_springs.iter_mut())
+        {
+            spring.rest_length *= factor;
+        }
+        self.wake();
+    }
+
+    // Rest positions move too so reset() returns to the transformed shape
+    fn transform_points(&mut self, f: impl Fn(Vec3) -> Vec3) {
+        for mass in &mut self.masses {
+            mass.position = f(mass.position);
+            mass.previous_position = f(mass.previous_position);
+        }
+        for rest_position in &mut self.rest_positions {
+            *rest_position = f(*rest_position);
+        }
+    }
 
-                let v1 = self.masses[right].position - self.masses[idx].position;
-                let v2 = self.masses[down].position - self.masses[idx].position;
-                let normal = v1.cross(&v2).normalize();
+    // Over the structural springs; max_index indexes structural_springs
+    pub fn strain_stats(&self) -> StrainStats {
+        let mut stats = StrainStats::default();
+        if self.structural_springs.is_empty() {
+            return stats;
+        }
 
-                self.masses[idx].normal = self.masses[idx].normal + normal;
-                self.masses[right].normal = self.masses[right].normal + normal;
-                self.masses[down].normal = self.masses[down].normal + normal;
-                self.masses[down_right].normal = self.masses[down_right].normal + normal;
+        let mut total = 0.0;
+        for (index, spring) in self.structural_springs.iter().enumerate() {
+            let strain = spring.strain(&self.masses);
+            total += strain;
+            if index == 0 || strain > stats.max {
+                stats.max = strain;
+                stats.max_index = index;
             }
         }
+        stats.mean = total / self.structural_springs.len() as Scalar;
+        stats
+    }
+
+    pub fn kinetic_energy(&self) -> Scalar {
+        self.masses
+            .iter()
+            .map(|mass| 0.5 * mass.mass * mass.velocity.length_squared())
+            .sum()
+    }
 
+    pub fn potential_energy(&self) -> Scalar {
+        let elastic: Scalar = self
+            .all_springs()
+            .map(|(spring, _)| spring.potential_energy(&self.masses))
+            .sum();
+        let gravitational: Scalar = self
+            .masses
+            .iter()
+            .map(|mass| -mass.mass * self.gravity.dot(&mass.position))
+            .sum();
+
+        elastic + gravitational
+    }
+
+    pub fn perturb(&mut self, amplitude: Scalar, seed: u64) {
+        let mut rng = XorShift::new(seed);
         for mass in &mut self.masses {
-            mass.normal = mass.normal.normalize();
+            let offset = Vec3::new(rng.next_signed(), rng.next_signed(), rng.next_signed());
+            if !mass.is_pinned() {
+                mass.position += offset * amplitude;
+                mass.previous_position = mass.position;
+            }
         }
     }
 
-    pub fn update(&mut self, dt: f32, damping: f32, mass_value: f32, wind: Vec3, wind_speed: f32) {
-        self.calculate_normals();
-        self.apply_forces(damping, wind, wind_speed);
+    pub fn apply_impulse(&mut self, index: usize, impulse: Vec3) {
+        if let Some(mass) = self.masses.get_mut(index) {
+            mass.apply_impulse(impulse);
+        }
+        self.wake();
+    }
+
+    pub fn apply_radial_impulse(&mut self, center: Vec3, strength: Scalar, radius: Scalar) {
+        self.wake();
         for mass in &mut self.masses {
-            mass.update(dt, mass_value);
+            let offset = mass.position - center;
+            let distance = offset.length();
+            if distance < radius {
+                let falloff = 1.0 - distance / radius;
+                mass.apply_impulse(offset.normalize() * strength * falloff);
+            }
         }
     }
 
-    fn apply_forces(&mut self, damping: f32, wind: Vec3, wind_speed: f32) {
-        for spring in &self.structural_springs {
-            spring.apply_force(&mut self.masses);
+    pub fn nearest_mass(&self, point: Vec3) -> usize {
+        self.masses
+            .iter()
+            .map(|mass| mass.position.distance_squared(&point))
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map_or(0, |(index, _)| index)
+    }
+
+    pub fn grab(&mut self, index: usize) {
+        self.release();
+        if let Some(mass) = self.masses.get_mut(index) {
+            self.grab = Some(Grab {
+                index,
+                target: mass.position,
+                was_pinned: mass.is_pinned(),
+            });
+            mass.set_pinned(true);
         }
+    }
+
+    pub fn drag_to(&mut self, world_pos: Vec3) {
+        if let Some(grab) = &mut self.grab {
+            grab.target = world_pos;
+        }
+    }
+
+    pub fn release(&mut self) {
+        if let Some(grab) = self.grab.take() {
+            self.masses[grab.index].set_pinned(grab.was_pinned);
+        }
+    }
+
+    // Anchored masses are pinned and follow their anchor every step. Unlike
+    // animated pins, each anchor is an absolute position for one mass.
+    pub fn set_anchor(&mut self, index: usize, pos: Vec3) {
+        if self.anchors.iter().any(|anchor| anchor.index == index) {
+            self.move_anchor(index, pos);
+        } else if let Some(mass) = self.masses.get_mut(index) {
+            self.anchors.push(Anchor {
+                index,
+                position: pos,
+                was_pinned: mass.is_pinned(),
+            });
+            mass.set_pinned(true);
+            self.wake();
+        }
+    }
+
+    pub fn move_anchor(&mut self, index: usize, pos: Vec3) {
+        if let Some(anchor) = self.anchors.iter_mut().find(|anchor| anchor.index == index) {
+            anchor.position = pos;
+            self.wake();
+        }
+    }
+
+    pub fn clear_anchor(&mut self, index: usize) {
+        if let Some(slot) = self.anchors.iter().position(|anchor| anchor.index == index) {
+            let anchor = self.anchors.remove(slot);
+            self.masses[index].set_pinned(anchor.was_pinned);
+            self.wake();
+        }
+    }
+
+    fn move_anchors(&mut self, dt: Scalar) {
+        for anchor in &self.anchors {
+            let mass = &mut self.masses[anchor.index];
+            mass.velocity = (anchor.position - mass.position) / dt;
+        }
+    }
+
+    fn move_grabbed(&mut self, dt: Scalar) {
+        if let Some(grab) = &self.grab {
+            let mass = &mut self.masses[grab.index];
+            mass.velocity = (grab.target - mass.position) / dt;
+        }
+    }
+
+    pub fn enable_trace(&mut self, index: usize) {
+        self.trace_index = Some(index);
+        self.trace.clear();
+    }
+
+    pub fn trace_csv(&self) -> String {
+        let mut csv = String::from("time,x,y,z\n");
+        for (time, p) in &self.trace {
+            writeln!(csv, "{},{},{},{}", time, p.x, p.y, p.z).unwrap();
+        }
+        csv
+    }
+
+    pub fn enable_force_report(&mut self) {
+        self.force_report = Some(ForceReport::default());
+    }
+
+    pub fn disable_force_report(&mut self) {
+        self.force_report = None;
+    }
+
+    // Only Euler and Verlet steps fill the report; RK4 evaluates forces at
+    // intermediate states that don't belong to any one step
+    pub fn last_force_report(&self) -> Option<ForceReport> {
+        self.force_report
+    }
+
+    pub fn export_obj(&self) -> String {
+        let mut obj = String::new();
 
-        for spring in &self.shear_springs {
-            spring.apply_force(&mut self.masses);
+        for mass in &self.masses {
+            let p = mass.position;
+            writeln!(obj, "v {} {} {}", p.x, p.y, p.z).unwrap();
         }
 
-        for spring in &self.bend_springs {
-            spring.apply_force(&mut self.masses);
+        // OBJ vertex indices are 1-based
+        for [a, b, c] in self.triangles() {
+            writeln!(obj, "f {} {} {}", a + 1, b + 1, c + 1).unwrap();
         }
 
+        obj
+    }
+
+    pub fn pin(&mut self, row: usize, col: usize) {
+        self.set_pinned(row, col, true);
+    }
+
+    pub fn unpin(&mut self, row: usize, col: usize) {
+        self.set_pinned(row, col, false);
+    }
+
+    pub fn pin_where(&mut self, pred: impl Fn(&Mass) -> bool) {
+        self.wake();
         for mass in &mut self.masses {
-            mass.apply_force(GRAVITY); // gravity
-            mass.apply_force(-damping * mass.velocity); // damping
-            mass.apply_force(wind_speed * mass.normal.dot(&(wind - mass.velocity)) * mass.normal); // Wind
+            if pred(mass) {
+                mass.set_pinned(true);
+            }
         }
     }
-}
 
-pub struct Mass {
-    pub position: Vec3,
-    pub velocity: Vec3,
-    pub acceleration: Vec3,
-    pub normal: Vec3,
-    pub pinned: bool,
-}
+    // Bounds are inclusive
+    pub fn pin_region(&mut self, min: Vec3, max: Vec3) {
+        self.pin_where(|mass| {
+            let p = mass.position;
+            (min.x..=max.x).contains(&p.x)
+                && (min.y..=max.y).contains(&p.y)
+                && (min.z..=max.z).contains(&p.z)
+        });
+    }
 
-impl Mass {
-    pub fn new(position: Vec3, pinned: bool) -> Self {
-        Mass {
-            position: position,
-            velocity: Vec3::zero(),
-            acceleration: Vec3::zero(),
-            normal: Vec3::zero(),
-            pinned: pinned,
+    pub fn unpin_all(&mut self) {
+        self.wake();
+        for mass in &mut self.masses {
+            mass.set_pinned(false);
         }
     }
 
-    pub fn apply_force(&mut self, force: Vec3) {
-        if !self.pinned {
-            self.acceleration = self.acceleration + force;
+    // Replaces any existing pins with the pattern. Row 0 is the top edge.
+    pub fn apply_pin_pattern(&mut self, pattern: PinPattern) {
+        self.unpin_all();
+        let (last_row, last_col) = (self.rows.saturating_sub(1), self.cols.saturating_sub(1));
+        match pattern {
+            PinPattern::None => {}
+            PinPattern::TopCorners => {
+                self.pin(0, 0);
+                self.pin(0, last_col);
+            }
+            PinPattern::TopEdge => (0..self.cols).for_each(|col| self.pin(0, col)),
+            PinPattern::LeftEdge => (0..self.rows).for_each(|row| self.pin(row, 0)),
+            PinPattern::AllCorners => {
+                self.pin(0, 0);
+                self.pin(0, last_col);
+                self.pin(last_row, 0);
+                self.pin(last_row, last_col);
+            }
         }
     }
 
-    pub fn update(&mut self, dt: f32, mass: f32) {
-        if !self.pinned {
-            let new_acc = self.acceleration / mass;
-            self.velocity = self.velocity + new_acc * dt;
-            self.position = self.position + self.velocity * dt;
-            self.acceleration = Vec3::zero();
+    pub fn set_mass(&mut self, row: usize, col: usize, mass: Scalar) {
+        if let Some(m) = self.mass_at_mut(row, col) {
+            m.set_mass(mass);
         }
+        self.wake();
     }
-}
 
-pub struct Spring {
-    pub a: usize,
-    pub b: usize,
-    pub rest_length: f32,
-    pub stiffness: f32,
-}
+    fn set_pinned(&mut self, row: usize, col: usize, pinned: bool) {
+        if let Some(mass) = self.mass_at_mut(row, col) {
+            mass.set_pinned(pinned);
+        }
+        self.wake();
+    }
 
-impl Spring {
-    pub fn new(a: usize, b: usize, rest_length: f32, stiffness: f32) -> Self {
-        Spring {
-            a,
-            b,
-            rest_length,
-            stiffness,
+    pub fn index(&self, row: usize, col: usize) -> Option<usize> {
+        if self.grid_triangles && row < self.rows && col < self.cols {
+            Some(row * self.cols + col)
+        } else {
+            None
         }
     }
 
-    pub fn apply_force(&self, masses: &mut [Mass]) {
-        let distance = masses[self.b].position - masses[self.a].position;
-        let length = distance.length();
-        let force = self.stiffness * (length - self.rest_length) * distance.normalize();
+    pub fn coords(&self, index: usize) -> Option<(usize, usize)> {
+        if self.grid_triangles && index < self.rows * self.cols {
+            Some((index / self.cols, index % self.cols))
+        } else {
+            None
+        }
+    }
 
-        masses[self.a].apply_force(force);
-        masses[self.b].apply_force(-force);
+    pub fn mass_at(&self, row: usize, col: usize) -> Option<&Mass> {
+        self.index(row, col).map(|index| &self.masses[index])
+    }
+
+    pub fn mass_at_mut(&mut self, row: usize, col: usize) -> Option<&mut Mass> {
+        self.index(row, col).map(|index| &mut self.masses[index])
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    // Walks the grid perimeter clockwise (viewed from above) starting at index 0.
+    // A cylinder's seam columns are included even though they are not edges.
+    pub fn boundary_indices(&self) -> Vec<usize> {
+        if !self.grid_triangles {
+            return Vec::new();
+        }
+
+        let (rows, cols) = (self.rows, self.cols);
+        let mut indices: Vec<usize> = (0..cols).collect();
+        indices.extend((1..rows).map(|i| i * cols + cols - 1));
+        if rows > 1 {
+            indices.extend((0..cols.saturating_sub(1)).rev().map(|j| (rows - 1) * cols + j));
+        }
+        if cols > 1 {
+            indices.extend((1..rows.saturating_sub(1)).rev().map(|i| i * cols));
+        }
+        indices
+    }
+
+    pub fn boundary_springs(&self) -> Vec<(usize, usize)> {
+        let indices = self.boundary_indices();
+        let mut edges: Vec<(usize, usize)> = indices.windows(2).map(|w| (w[0], w[1])).collect();
+
+        // A single row or column is a line, not a loop
+        if self.rows > 1 && self.cols > 1 && !indices.is_empty() {
+            edges.push((indices[indices.len() - 1], indices[0]));
+        }
+        edges
+    }
+
+    pub fn edge_indices(&self, edge: Edge) -> Vec<usize> {
+        if !self.grid_triangles {
+            return Vec::new();
+        }
+
+        let (rows, cols) = (self.rows, self.cols);
+        match edge {
+            Edge::Top => (0..cols).collect(),
+            Edge::Bottom => (0..cols).map(|j| (rows - 1) * cols + j).collect(),
+            Edge::Left => (0..rows).map(|i| i * cols).collect(),
+            Edge::Right => (0..rows).map(|i| i * cols + cols - 1).collect(),
+        }
+    }
+
+    // Appends other's masses and springs and sews the two edges together with
+    // structural springs. Seam rest lengths come from the current placement, so
+    // position the panel first. Grid accessors (rows, cols, index, edges) keep
+    // describing this cloth's original panel only. Edges of different lengths
+    // leave both cloths untouched.
+    pub fn stitch(
+        &mut self,
+        other: &Cloth,
+        self_edge: Edge,
+        other_edge: Edge,
+    ) -> Result<(), StitchError> {
+        let self_seam = self.edge_indices(self_edge);
+        let other_seam = other.edge_indices(other_edge);
+        if self_seam.len() != other_seam.len() {
+            return Err(StitchError {
+                self_edge_len: self_seam.len(),
+                other_edge_len: other_seam.len(),
+            });
+        }
+        self.spring_colors.clear();
+
+        let offset = self.masses.len();
+        self.masses.extend(other.masses.iter().cloned());
+        self.rest_positions.extend(other.rest_positions.iter().copied());
+        for (springs, other_springs) in [
+            (&mut self.structural_springs, &other.structural_springs),
+            (&mut self.shear_springs, &other.shear_springs),
+            (&mut self.bend_springs, &other.bend_springs),
+        ] {
+            springs.extend(other_springs.iter().map(|spring| Spring {
+                a: spring.a + offset,
+                b: spring.b + offset,
+                ..*spring
+            }));
+        }
+        self.extra_triangles
+            .extend(other.triangles().map(|triangle| triangle.map(|index| index + offset)));
+
+        let template = self.structural_springs.first().copied();
+        let stiffness = template.map_or(DEFAULT_STIFFNESS, |spring| spring.stiffness);
+        let seam: Vec<(usize, usize)> = self_seam
+            .into_iter()
+            .zip(other_seam.into_iter().map(|index| index + offset))
+            .collect();
+        for &(a, b) in &seam {
+            let mut spring = self.spring_between(a, b, stiffness);
+            if let Some(template) = template {
+                spring.damping = template.damping;
+                spring.max_strain = template.max_strain;
+            }
+            self.structural_springs.push(spring);
+        }
+
+        // Close the gap between the panels, facing each triangle the same way as
+        // the normals around it so shading and wind stay consistent
+        self.calculate_normals();
+        for pair in seam.windows(2) {
+            let [(a, b), (c, d)] = [pair[0], pair[1]];
+            for mut triangle in [[a, b, c], [c, b, d]] {
+                let around: Vec3 = triangle.iter().map(|&index| self.masses[index].normal).sum();
+                if self.triangle_cross(triangle).dot(&around) < 0.0 {
+                    triangle.swap(1, 2);
+                }
+                self.extra_triangles.push(triangle);
+            }
+        }
+        self.calculate_normals();
+        self.wake();
+        Ok(())
+    }
+
+    pub fn all_springs(&self) -> impl Iterator<Item = (&Spring, SpringKind)> {
+        [
+            (&self.structural_springs, SpringKind::Structural),
+            (&self.shear_springs, SpringKind::Shear),
+            (&self.bend_springs, SpringKind::Bend),
+        ]
+        .into_iter()
+        .flat_map(|(springs, kind)| springs.iter().map(move |spring| (spring, kind)))
+    }
+
+    // Greedy graph coloring: each spring takes the lowest color not yet used at
+    // either end. Within a color the springs touch disjoint masses, so with rayon
+    // each color is applied in parallel without an accumulation buffer.
+    pub fn color_springs(&mut self) -> Vec<Vec<usize>> {
+        let mut colors: Vec<Vec<usize>> = Vec::new();
+        let mut used: Vec<Vec<usize>> = vec![Vec::new(); self.masses.len()];
+        for (index, (spring, _)) in self.all_springs().enumerate() {
+            let color = (0..)
+                .find(|color| !used[spring.a].contains(color) && !used[spring.b].contains(color))
+                .unwrap();
+            if color == colors.len() {
+                colors.push(Vec::new());
+            }
+            colors[color].push(index);
+            used[spring.a].push(color);
+            used[spring.b].push(color);
+        }
+        self.spring_colors = colors.clone();
+        colors
+    }
+
+    // Removes the mass with every spring and triangle touching it. Indices above
+    // it shift down by one, so remove several masses in descending index order.
+    // The grid is gone afterwards, so row/column lookups return None and edge and
+    // boundary queries return nothing.
+    pub fn remove_mass(&mut self, index: usize) {
+        if index >= self.masses.len() {
+            return;
+        }
+
+        self.spring_colors.clear();
+        let remap = |i: usize| if i > index { i - 1 } else { i };
+        self.extra_triangles = self
+            .triangles()
+            .filter(|triangle| !triangle.contains(&index))
+            .map(|triangle| triangle.map(remap))
+            .collect();
+        self.grid_triangles = false;
+
+        self.masses.remove(index);
+        self.rest_positions.remove(index);
+        for springs in [
+            &mut self.structural_springs,
+            &mut self.shear_springs,
+            &mut self.bend_springs,
+        ] {
+            springs.retain(|spring| spring.a != index && spring.b != index);
+            for spring in springs.iter_mut() {
+                spring.a = remap(spring.a);
+                spring.b = remap(spring.b);
+            }
+        }
+
+        if self.grab.as_ref().is_some_and(|grab| grab.index == index) {
+            self.grab = None;
+        }
+        if let Some(grab) = &mut self.grab {
+            grab.index = remap(grab.index);
+        }
+        self.anchors.retain(|anchor| anchor.index != index);
+        for anchor in &mut self.anchors {
+            anchor.index = remap(anchor.index);
+        }
+        self.trace_index = self
+            .trace_index
+            .filter(|&traced| traced != index)
+            .map(remap);
+        self.wake();
+    }
+
+    pub fn shear_springs(&self) -> &[Spring] {
+        &self.shear_springs
+    }
+
+    pub fn bend_springs(&self) -> &[Spring] {
+        &self.bend_springs
+    }
+
+    pub fn gravity(&self) -> Vec3 {
+        self.gravity
+    }
+
+    pub fn set_gravity(&mut self, g: Vec3) {
+        self.gravity = g;
+        self.wake();
+    }
+
+    pub fn damping(&self) -> Scalar {
+        self.damping
+    }
+
+    pub fn set_damping(&mut self, damping: Scalar) {
+        self.damping = damping;
+        self.wake();
+    }
+
+    pub fn set_air_drag(&mut self, coeff: Scalar) {
+        self.air_drag = coeff;
+        self.wake();
+    }
+
+    pub fn integrator(&self) -> Integrator {
+        self.integrator
+    }
+
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        self.integrator = integrator;
+    }
+
+    pub fn wind_model(&self) -> WindModel {
+        self.wind_model
+    }
+
+    pub fn set_wind_model(&mut self, wind_model: WindModel) {
+        self.wind_model = wind_model;
+        self.wake();
+    }
+
+    pub fn bend_model(&self) -> BendModel {
+        self.bend_model
+    }
+
+    pub fn set_bend_model(&mut self, bend_model: BendModel) {
+        self.bend_model = bend_model;
+        self.wake();
+    }
+
+    pub fn solver_iterations(&self) -> usize {
+        self.solver_iterations
+    }
+
+    // Disabled families keep their springs, they just stop applying forces
+    pub fn set_enable_shear(&mut self, enabled: bool) {
+        self.enable_shear = enabled;
+        self.wake();
+    }
+
+    pub fn set_enable_bend(&mut self, enabled: bool) {
+        self.enable_bend = enabled;
+        self.wake();
+    }
+
+    pub fn set_solver_iterations(&mut self, iterations: usize) {
+        self.solver_iterations = iterations.max(1);
+    }
+
+    pub fn set_substeps(&mut self, substeps: usize) {
+        self.substeps = substeps.max(1);
+    }
+
+    pub fn add_collider(&mut self, collider: Collider) {
+        self.colliders.push(collider);
+        self.wake();
+    }
+
+    pub fn colliders(&self) -> &[Collider] {
+        &self.colliders
+    }
+
+    // For instant edits: teleporting, replacing or removing colliders
+    pub fn colliders_mut(&mut self) -> &mut Vec<Collider> {
+        self.wake();
+        &mut self.colliders
+    }
+
+    // Sweeps the collider by offset over the next update. The motion is split
+    // across substeps, so a fast collider pushes the cloth instead of jumping
+    // past it.
+    pub fn move_collider(&mut self, index: usize, offset: Vec3) {
+        self.collider_sweeps.push((index, offset));
+        self.wake();
+    }
+
+    pub fn set_ground(&mut self, y: Scalar) {
+        self.add_collider(Collider::Plane {
+            point: Vec3::new(0.0, y, 0.0),
+            normal: Vec3::new(0.0, 1.0, 0.0),
+            friction: 0.0,
+            restitution: 0.0,
+            mask: u32::MAX,
+        });
+    }
+
+    pub fn set_collision_mask(&mut self, mask: u32) {
+        self.collision_mask = mask;
+        self.wake();
+    }
+
+    pub fn set_spring_damping(&mut self, damping: Scalar) {
+        for spring in self
+            .structural_springs
+            .iter_mut()
+            .chain(self.shear_springs.iter_mut())
+            .chain(self.bend_springs.iter_mut())
+        {
+            spring.damping = damping;
+        }
+        self.wake();
+    }
+
+    pub fn set_stiffness(&mut self, stiffness: impl Into<SpringStiffness>) {
+        let stiffness = stiffness.into();
+        for (springs, value) in [
+            (&mut self.structural_springs, stiffness.structural),
+            (&mut self.shear_springs, stiffness.shear),
+            (&mut self.bend_springs, stiffness.bend),
+        ] {
+            for spring in springs.iter_mut() {
+                spring.stiffness = value;
+            }
+        }
+        self.bend_stiffness = stiffness.bend;
+        self.wake();
+    }
+
+    pub fn scale_stiffness(&mut self, factor: Scalar) {
+        for spring in self
+            .structural_springs
+            .iter_mut()
+            .chain(self.shear_springs.iter_mut())
+            .chain(self.bend_springs.iter_mut())
+        {
+            spring.stiffness *= factor;
+        }
+        self.bend_stiffness *= factor;
+        self.wake();
+    }
+
+    pub fn bake_rest_lengths(&mut self) {
+        let masses = &self.masses;
+        for spring in self
+            .structural_springs
+            .iter_mut()
+            .chain(self.shear_springs.iter_mut())
+            .chain(self.bend_springs.iter_mut())
+        {
+            spring.rest_length = masses[spring.a].position.distance(&masses[spring.b].position);
+        }
+        self.wake();
+    }
+
+    pub fn set_wind_fn(&mut self, wind_fn: impl FnMut(Scalar) -> Vec3 + 'static) {
+        self.wind_fn = Some(Box::new(wind_fn));
+    }
+
+    pub fn clear_wind_fn(&mut self) {
+        self.wind_fn = None;
+    }
+
+    // Moves every pinned mass to its rest position plus f(time) each step
+    pub fn animate_pins(&mut self, f: impl Fn(Scalar) -> Vec3 + 'static) {
+        self.pin_animation = Some(Box::new(f));
+    }
+
+    pub fn clear_pin_animation(&mut self) {
+        self.pin_animation = None;
+        // Stop the pins where they are instead of letting them coast
+        for mass in &mut self.masses {
+            if mass.is_pinned() {
+                mass.velocity = Vec3::zero();
+            }
+        }
+    }
+
+    fn move_pins(&mut self, dt: Scalar) {
+        let Some(animation) = &self.pin_animation else {
+            return;
+        };
+
+        let offset = animation(self.time + dt);
+        let grabbed = self.grab.as_ref().map(|grab| grab.index);
+        for (index, (mass, rest_position)) in
+            self.masses.iter_mut().zip(&self.rest_positions).enumerate()
+        {
+            let anchored = self.anchors.iter().any(|anchor| anchor.index == index);
+            if mass.is_pinned() && Some(index) != grabbed && !anchored {
+                let target = *rest_position + offset;
+                mass.velocity = (target - mass.position) / dt;
+            }
+        }
+    }
+
+    pub fn is_stable(&self) -> bool {
+        self.masses.iter().all(|mass| mass.position.is_finite())
+            && self.strain_stats().max < MAX_STABLE_STRAIN
+    }
+
+    // Replaces the wind passed to update with a noise field around base
+    pub fn set_turbulent_wind(&mut self, base: Vec3, strength: Scalar, scale: Scalar) {
+        self.turbulence = Some(Turbulence {
+            base,
+            strength,
+            scale,
+        });
+        self.wake();
+    }
+
+    pub fn clear_turbulent_wind(&mut self) {
+        self.turbulence = None;
+        self.wake();
+    }
+
+    pub fn wind_at(&self, position: Vec3) -> Option<Vec3> {
+        self.turbulence
+            .as_ref()
+            .map(|turbulence| turbulence.sample(position, self.time))
+    }
+
+    pub fn set_max_stretch(&mut self, ratio: Scalar) {
+        self.max_stretch = Some(ratio);
+        self.wake();
+    }
+
+    pub fn set_max_velocity(&mut self, max_velocity: Option<Scalar>) {
+        self.max_velocity = max_velocity;
+    }
+
+    pub fn set_pressure(&mut self, stiffness: Scalar, rest_volume: Scalar) {
+        self.pressure = Some((stiffness, rest_volume));
+        self.wake();
+    }
+
+    pub fn clear_pressure(&mut self) {
+        self.pressure = None;
+        self.wake();
+    }
+
+    pub fn set_adaptive(&mut self, max_strain: Scalar, min_dt: Scalar) {
+        self.adaptive = Some((max_strain, min_dt));
+    }
+
+    pub fn clear_adaptive(&mut self) {
+        self.adaptive = None;
+    }
+
+    // The cloth falls asleep once no mass has moved faster than the threshold
+    // speed for the given number of consecutive steps
+    pub fn set_sleep(&mut self, speed_threshold: Scalar, steps: u32) {
+        self.sleep = Some((speed_threshold, steps));
+    }
+
+    pub fn clear_sleep(&mut self) {
+        self.sleep = None;
+        self.wake();
+    }
+
+    pub fn is_asleep(&self) -> bool {
+        self.asleep.is_some()
+    }
+
+    pub fn wake(&mut self) {
+        self.asleep = None;
+        self.still_steps = 0;
+    }
+
+    pub fn set_self_collision(&mut self, enabled: bool, radius: Scalar) {
+        self.self_collision_radius = if enabled { Some(radius) } else { None };
+        self.wake();
+    }
+
+    pub fn set_tear_threshold(&mut self, strain: Scalar) {
+        for spring in self
+            .structural_springs
+            .iter_mut()
+            .chain(self.shear_springs.iter_mut())
+            .chain(self.bend_springs.iter_mut())
+        {
+            spring.max_strain = Some(strain);
+        }
+        self.wake();
+    }
+
+    // Reports each torn pair once, lower index first, although the grid holds a
+    // spring from each end
+    fn tear_springs(&mut self) -> Vec<(usize, usize)> {
+        let masses = &self.masses;
+        let mut torn = Vec::new();
+        for springs in [
+            &mut self.structural_springs,
+            &mut self.shear_springs,
+            &mut self.bend_springs,
+        ] {
+            springs.retain(|spring| {
+                let is_torn = spring.is_torn(masses);
+                let pair = (spring.a.min(spring.b), spring.a.max(spring.b));
+                if is_torn && !torn.contains(&pair) {
+                    torn.push(pair);
+                }
+                !is_torn
+            });
+        }
+        if !torn.is_empty() {
+            self.spring_colors.clear();
+        }
+        torn
+    }
+
+    pub fn calculate_normals(&mut self) {
+        let normals = self.vertex_normals();
+        for (mass, normal) in self.masses.iter_mut().zip(normals) {
+            mass.normal = normal;
+        }
+    }
+
+    // Each grid quad is split into the triangles (idx, down, right) and
+    // (right, down, down_right). With the initial layout (columns along +x, rows
+    // along +z) this winding makes every normal point along +y.
+    pub fn triangles(&self) -> impl Iterator<Item = [usize; 3]> + '_ {
+        let quad_cols = if self.wrap_cols {
+            self.cols
+        } else {
+            self.cols.saturating_sub(1)
+        };
+
+        let quad_rows = if self.grid_triangles {
+            self.rows.saturating_sub(1)
+        } else {
+            0
+        };
+
+        (0..quad_rows).flat_map(move |i| {
+            (0..quad_cols).flat_map(move |j| {
+                let idx = i * self.cols + j;
+                let right = i * self.cols + (j + 1) % self.cols;
+                let down = idx + self.cols;
+                let down_right = right + self.cols;
+
+                [[idx, down, right], [right, down, down_right]]
+            })
+        })
+        .chain(self.extra_triangles.iter().copied())
+    }
+
+    fn triangle_cross(&self, triangle: [usize; 3]) -> Vec3 {
+        let [a, b, c] = triangle.map(|index| self.masses[index].position);
+        (b - a).cross(&(c - a))
+    }
+
+    // Divergence theorem over the triangles, signed by their winding. Only
+    // meaningful for a closed surface; open edges are treated as if capped
+    // through the origin.
+    fn signed_volume(&self, masses: &[Mass]) -> Scalar {
+        self.triangles()
+            .map(|[a, b, c]| {
+                let [a, b, c] = [a, b, c].map(|index| masses[index].position);
+                a.dot(&b.cross(&c)) / 6.0
+            })
+            .sum()
+    }
+
+    pub fn volume(&self) -> Scalar {
+        self.signed_volume(&self.masses).abs()
+    }
+
+    pub fn surface_area(&self) -> Scalar {
+        self.triangles()
+            .map(|triangle| 0.5 * self.triangle_cross(triangle).length())
+            .sum()
+    }
+
+    pub fn rest_area(&self) -> Scalar {
+        self.triangles()
+            .map(|triangle| {
+                let [a, b, c] = triangle.map(|index| self.rest_positions[index]);
+                0.5 * (b - a).cross(&(c - a)).length()
+            })
+            .sum()
+    }
+
+    // Largest vertical distance of any mass from where it started, which for a
+    // horizontal sheet is the sag below its initial plane
+    pub fn max_deflection(&self) -> Scalar {
+        self.masses
+            .iter()
+            .zip(&self.rest_positions)
+            .map(|(mass, rest_position)| (mass.position.y - rest_position.y).abs())
+            .fold(0.0, Scalar::max)
+    }
+
+    pub fn face_normals(&self) -> Vec<Vec3> {
+        self.triangles()
+            .map(|triangle| self.triangle_cross(triangle).normalize())
+            .collect()
+    }
+
+    pub fn vertex_normals(&self) -> Vec<Vec3> {
+        let mut normals = vec![Vec3::zero(); self.masses.len()];
+
+        // The unnormalized cross product is twice the triangle area, which gives
+        // the area weighting for free
+        for triangle in self.triangles() {
+            let weighted_normal = self.triangle_cross(triangle);
+            for index in triangle {
+                normals[index] += weighted_normal;
+            }
+        }
+
+        normals.iter().map(|normal| normal.normalize()).collect()
+    }
+
+    pub fn update(&mut self, dt: Scalar, wind: Vec3, wind_speed: Scalar) -> StepReport {
+        self.step(dt, wind, wind_speed, None)
+    }
+
+    // Adds force_fn(mass) to every mass whenever forces are evaluated, as a hook
+    // for coupling the cloth to outside effects
+    pub fn update_with(
+        &mut self,
+        dt: Scalar,
+        wind: Vec3,
+        wind_speed: Scalar,
+        force_fn: impl Fn(&Mass) -> Vec3,
+    ) -> StepReport {
+        self.step(dt, wind, wind_speed, Some(&force_fn))
+    }
+
+    fn step(
+        &mut self,
+        dt: Scalar,
+        wind: Vec3,
+        wind_speed: Scalar,
+        external: Option<&dyn Fn(&Mass) -> Vec3>,
+    ) -> StepReport {
+        let wind = match &mut self.wind_fn {
+            Some(wind_fn) => wind_fn(self.time),
+            None => wind,
+        };
+
+        if let Some(sleeping_wind) = self.asleep {
+            let moved = self.grab.is_some() || self.pin_animation.is_some() || external.is_some();
+            if moved || sleeping_wind != (wind, wind_speed) {
+                self.wake();
+            } else {
+                self.advance_clock(dt);
+                return StepReport::default();
+            }
+        }
+
+        self.move_grabbed(dt);
+        self.move_anchors(dt);
+        self.move_pins(dt);
+        let torn_springs = self.tear_springs();
+        self.calculate_normals();
+
+        self.collider_velocities = vec![Vec3::zero(); self.colliders.len()];
+        for (index, offset) in std::mem::take(&mut self.collider_sweeps) {
+            if let Some(velocity) = self.collider_velocities.get_mut(index) {
+                *velocity += offset / dt;
+            }
+        }
+
+        // Smaller substeps keep fast contacts from skipping past the cloth
+        let substep_dt = dt / self.substeps as Scalar;
+        let mut collisions = 0;
+        for _ in 0..self.substeps {
+            for (collider, velocity) in self.colliders.iter_mut().zip(&self.collider_velocities) {
+                collider.translate(*velocity * substep_dt);
+            }
+            collisions += self.adaptive_substep(substep_dt, wind, wind_speed, external);
+        }
+        self.collider_velocities.clear();
+        self.sanitize();
+        self.update_sleep(wind, wind_speed);
+        self.advance_clock(dt);
+
+        StepReport {
+            torn_springs,
+            collisions,
+        }
+    }
+
+    // Counts a step and records its trace sample, whether or not the cloth slept
+    // through it
+    fn advance_clock(&mut self, dt: Scalar) {
+        self.steps += 1;
+        self.time += dt;
+        if let Some(mass) = self.trace_index.and_then(|index| self.masses.get(index)) {
+            self.trace.push((self.time, mass.position));
+        }
+    }
+
+    fn update_sleep(&mut self, wind: Vec3, wind_speed: Scalar) {
+        let Some((speed_threshold, steps)) = self.sleep else {
+            return;
+        };
+        // Turbulence changes the wind every step, so the cloth never settles for good
+        if self.turbulence.is_some() {
+            self.still_steps = 0;
+            return;
+        }
+
+        let threshold_squared = speed_threshold * speed_threshold;
+        if self.masses.iter().any(|mass| mass.velocity.length_squared() >= threshold_squared) {
+            self.still_steps = 0;
+            return;
+        }
+
+        self.still_steps += 1;
+        if self.still_steps >= steps {
+            // Settle exactly, so waking up doesn't resume the residual motion
+            for mass in &mut self.masses {
+                mass.velocity = Vec3::zero();
+                mass.previous_position = mass.position;
+            }
+            self.asleep = Some((wind, wind_speed));
+        }
+    }
+
+    // Retries a step that overstretches the cloth as two half steps, down to the
+    // minimum dt, after which the step is accepted as is
+    fn adaptive_substep(
+        &mut self,
+        dt: Scalar,
+        wind: Vec3,
+        wind_speed: Scalar,
+        external: Option<&dyn Fn(&Mass) -> Vec3>,
+    ) -> usize {
+        let Some((max_strain, min_dt)) = self.adaptive else {
+            return self.substep(dt, wind, wind_speed, external);
+        };
+        if dt * 0.5 < min_dt {
+            return self.substep(dt, wind, wind_speed, external);
+        }
+
+        let saved = self.masses.clone();
+        let collisions = self.substep(dt, wind, wind_speed, external);
+        if self.strain_stats().max <= max_strain {
+            return collisions;
+        }
+
+        self.masses = saved;
+        self.subdivisions += 1;
+        self.adaptive_substep(dt * 0.5, wind, wind_speed, external)
+            + self.adaptive_substep(dt * 0.5, wind, wind_speed, external)
+    }
+
+    fn substep(
+        &mut self,
+        dt: Scalar,
+        wind: Vec3,
+        wind_speed: Scalar,
+        external: Option<&dyn Fn(&Mass) -> Vec3>,
+    ) -> usize {
+        match self.integrator {
+            Integrator::Rk4 => self.rk4_step(dt, wind, wind_speed, external),
+            integrator => {
+                self.apply_forces(wind, wind_speed, external);
+                let (damping, max_velocity) = (self.damping, self.max_velocity);
+                for_each_mass(&mut self.masses, |mass| {
+                    if integrator == Integrator::Verlet {
+                        mass.damp_implicitly(damping, dt);
+                    }
+                    mass.update(dt, integrator, max_velocity)
+                });
+            }
+        }
+        self.relax_springs(dt);
+        self.limit_stretch(dt);
+        self.resolve_self_collisions(dt);
+        self.resolve_collisions(dt)
+    }
+
+    fn resolve_self_collisions(&mut self, dt: Scalar) {
+        let Some(radius) = self.self_collision_radius else {
+            return;
+        };
+
+        let mut hash = SpatialHash::new(radius);
+        for (index, mass) in self.masses.iter().enumerate() {
+            hash.insert(index, mass.position);
+        }
+
+        // Masses joined by a structural or shear spring sit closer than the
+        // radius by design, so only non-adjacent pairs collide. Torn springs
+        // drop out, which lets their ends collide again.
+        let adjacent: HashSet<(usize, usize)> = self
+            .structural_springs
+            .iter()
+            .chain(&self.shear_springs)
+            .map(|spring| (spring.a.min(spring.b), spring.a.max(spring.b)))
+            .collect();
+
+        for i in 0..self.masses.len() {
+            for j in hash.query(self.masses[i].position) {
+                if j > i && !adjacent.contains(&(i, j)) {
+                    self.separate(i, j, radius, dt);
+                }
+            }
+        }
+    }
+
+    fn separate(&mut self, i: usize, j: usize, radius: Scalar, dt: Scalar) {
+        let weight_i = self.masses[i].inv_mass;
+        let weight_j = self.masses[j].inv_mass;
+        let total_weight = weight_i + weight_j;
+        if total_weight == 0.0 {
+            return;
+        }
+
+        let delta = self.masses[j].position - self.masses[i].position;
+        let distance = delta.length();
+        if distance >= radius || distance == 0.0 {
+            return;
+        }
+
+        let normal = delta / distance;
+        let correction = (radius - distance) / total_weight;
+        self.masses[i].position -= weight_i * correction * normal;
+        self.masses[j].position += weight_j * correction * normal;
+
+        // Remove the approaching part of the relative velocity
+        let approach = (self.masses[j].velocity - self.masses[i].velocity).dot(&normal);
+        if approach < 0.0 {
+            let impulse = approach / total_weight;
+            self.masses[i].velocity += weight_i * impulse * normal;
+            self.masses[j].velocity -= weight_j * impulse * normal;
+        }
+
+        for index in [i, j] {
+            let mass = &mut self.masses[index];
+            mass.previous_position = mass.position - mass.velocity * dt;
+        }
+    }
+
+    fn relax_springs(&mut self, dt: Scalar) {
+        // The first iteration is the force pass, the rest project structural springs
+        if self.solver_iterations <= 1 {
+            return;
+        }
+
+        for _ in 1..self.solver_iterations {
+            for spring in &self.structural_springs {
+                spring.relax(&mut self.masses);
+            }
+        }
+
+        self.sync_velocities(dt);
+    }
+
+    fn limit_stretch(&mut self, dt: Scalar) {
+        let Some(max_stretch) = self.max_stretch else {
+            return;
+        };
+
+        // Clamping one spring can stretch its neighbours again, so repeat the pass
+        // until every spring is within the limit
+        for _ in 0..MAX_STRETCH_PASSES {
+            let mut clamped = false;
+            for spring in &self.structural_springs {
+                clamped |= spring.limit_stretch(&mut self.masses, max_stretch);
+            }
+            if !clamped {
+                break;
+            }
+        }
+
+        self.sync_velocities(dt);
+    }
+
+    fn sync_velocities(&mut self, dt: Scalar) {
+        for mass in &mut self.masses {
+            if !mass.is_pinned() {
+                mass.velocity = (mass.position - mass.previous_position) / dt;
+                if let Some(max_velocity) = self.max_velocity {
+                    mass.velocity = mass.velocity.clamp_length(max_velocity);
+                }
+            }
+        }
+    }
+
+    fn resolve_collisions(&mut self, dt: Scalar) -> usize {
+        let mut collisions = 0;
+        let collision_mask = self.collision_mask;
+        for (index, collider) in self
+            .colliders
+            .iter()
+            .enumerate()
+            .filter(|(_, collider)| collider.mask() & collision_mask != 0)
+        {
+            let velocity = self.collider_velocities.get(index).copied().unwrap_or(Vec3::ZERO);
+            for mass in &mut self.masses {
+                if !mass.is_pinned() && collider.resolve_moving(mass, velocity, dt) {
+                    collisions += 1;
+                }
+            }
+        }
+        collisions
+    }
+
+    fn apply_forces(
+        &mut self,
+        wind: Vec3,
+        wind_speed: Scalar,
+        external: Option<&dyn Fn(&Mass) -> Vec3>,
+    ) {
+        let mut masses = std::mem::take(&mut self.masses);
+        let mut report = self.force_report.map(|_| ForceReport::default());
+        self.accumulate_forces(&mut masses, wind, wind_speed, external, report.as_mut());
+        self.masses = masses;
+        if report.is_some() {
+            self.force_report = report;
+        }
+    }
+
+    fn accumulate_forces(
+        &self,
+        masses: &mut [Mass],
+        wind: Vec3,
+        wind_speed: Scalar,
+        external: Option<&dyn Fn(&Mass) -> Vec3>,
+        report: Option<&mut ForceReport>,
+    ) {
+        // Each category is measured as the change it makes to the accumulated
+        // forces, so the force functions themselves stay untouched
+        let mut tally = report.map(|report| {
+            let before: Vec<Vec3> = masses.iter().map(|mass| mass.acceleration).collect();
+            (report, before)
+        });
+        let mut record = |masses: &[Mass], category: fn(&mut ForceReport) -> &mut Scalar| {
+            if let Some((report, before)) = &mut tally {
+                *category(report) += force_delta(masses, before);
+            }
+        };
+
+        self.apply_spring_forces(masses);
+        self.apply_dihedral_forces(masses);
+        record(masses, |report| &mut report.spring);
+
+        let gravity = self.gravity;
+        let damping = self.damping;
+        let air_drag = self.air_drag;
+        for_each_mass(masses, |mass| mass.apply_force(gravity * mass.mass));
+        record(masses, |report| &mut report.gravity);
+        for_each_mass(masses, |mass| {
+            mass.apply_force(-damping * mass.velocity); // damping
+            mass.apply_force(-air_drag * mass.velocity.length() * mass.velocity); // air drag
+        });
+        record(masses, |report| &mut report.damping);
+
+        self.apply_wind(masses, wind, wind_speed);
+        record(masses, |report| &mut report.wind);
+        self.apply_pressure(masses);
+        record(masses, |report| &mut report.pressure);
+
+        if let Some(force_fn) = external {
+            for mass in masses.iter_mut() {
+                let force = force_fn(mass);
+                mass.apply_force(force);
+            }
+            record(masses, |report| &mut report.external);
+        }
+    }
+
+    fn apply_pressure(&self, masses: &mut [Mass]) {
+        let Some((stiffness, rest_volume)) = self.pressure else {
+            return;
+        };
+
+        // Flip inward-wound surfaces so positive pressure always pushes outward
+        let signed_volume = self.signed_volume(masses);
+        let pressure = stiffness * (rest_volume - signed_volume.abs()) * signed_volume.signum();
+        for triangle in self.triangles() {
+            let [a, b, c] = triangle.map(|index| masses[index].position);
+            // Half the cross product is the area-weighted normal, split between corners
+            let force = (b - a).cross(&(c - a)) * (pressure / 6.0);
+            for index in triangle {
+                masses[index].apply_force(force);
+            }
+        }
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    fn apply_spring_forces(&self, masses: &mut [Mass]) {
+        for spring in &self.structural_springs {
+            spring.apply_force(masses);
+        }
+
+        for spring in self.active_shear_springs() {
+            spring.apply_force(masses);
+        }
+
+        for spring in self.active_bend_springs() {
+            spring.apply_force(masses);
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    fn apply_spring_forces(&self, masses: &mut [Mass]) {
+        if !self.spring_colors.is_empty() {
+            self.apply_colored_spring_forces(masses);
+            return;
+        }
+
+        // Forces are computed in parallel but accumulated in spring order, so the
+        // result matches the sequential path exactly
+        let snapshot = &*masses;
+        let forces: Vec<(usize, usize, Vec3)> = self
+            .structural_springs
+            .par_iter()
+            .chain(self.active_shear_springs().par_iter())
+            .chain(self.active_bend_springs().par_iter())
+            .map(|spring| (spring.a, spring.b, spring.force(snapshot)))
+            .collect();
+
+        for (a, b, force) in forces {
+            masses[a].apply_force(force);
+            masses[b].apply_force(-force);
+        }
+    }
+
+    // Every mass is touched by at most one spring per color, so each mass can
+    // add its own share of the force in parallel
+    #[cfg(feature = "rayon")]
+    fn apply_colored_spring_forces(&self, masses: &mut [Mass]) {
+        let springs: Vec<Option<&Spring>> = self
+            .all_springs()
+            .map(|(spring, kind)| self.is_active(kind).then_some(spring))
+            .collect();
+        let snapshot = masses.to_vec();
+        let mut ends: Vec<Option<(&Spring, Scalar)>> = vec![None; masses.len()];
+        for color in &self.spring_colors {
+            ends.fill(None);
+            for spring in color.iter().filter_map(|&index| springs[index]) {
+                ends[spring.a] = Some((spring, 1.0));
+                ends[spring.b] = Some((spring, -1.0));
+            }
+            masses.par_iter_mut().zip(&ends).for_each(|(mass, end)| {
+                if let Some((spring, sign)) = end {
+                    mass.apply_force(spring.force(&snapshot) * *sign);
+                }
+            });
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    fn is_active(&self, kind: SpringKind) -> bool {
+        match kind {
+            SpringKind::Structural => true,
+            SpringKind::Shear => self.enable_shear,
+            SpringKind::Bend => self.enable_bend && self.bend_model == BendModel::DistanceSpring,
+        }
+    }
+
+    fn active_shear_springs(&self) -> &[Spring] {
+        if self.enable_shear {
+            &self.shear_springs
+        } else {
+            &[]
+        }
+    }
+
+    fn active_bend_springs(&self) -> &[Spring] {
+        match self.bend_model {
+            BendModel::DistanceSpring if self.enable_bend => &self.bend_springs,
+            _ => &[],
+        }
+    }
+
+    // Each hinge is [wing, wing, edge start, edge end] for two triangles sharing
+    // an edge. A BTreeMap keeps the order, and so the float sums, deterministic.
+    fn hinges(&self) -> Vec<[usize; 4]> {
+        let mut wings: BTreeMap<(usize, usize), Vec<usize>> = BTreeMap::new();
+        for [a, b, c] in self.triangles() {
+            for (start, end, opposite) in [(a, b, c), (b, c, a), (c, a, b)] {
+                wings
+                    .entry((start.min(end), start.max(end)))
+                    .or_default()
+                    .push(opposite);
+            }
+        }
+
+        wings
+            .into_iter()
+            .filter(|(_, opposite)| opposite.len() == 2)
+            .map(|((start, end), opposite)| [opposite[0], opposite[1], start, end])
+            .collect()
+    }
+
+    fn apply_dihedral_forces(&self, masses: &mut [Mass]) {
+        if self.bend_model != BendModel::Dihedral || !self.enable_bend {
+            return;
+        }
+
+        for hinge in self.hinges() {
+            apply_dihedral_force(masses, hinge, self.bend_stiffness);
+        }
+    }
+
+    // Turbulence replaces the wind direction but is still scaled by wind_speed
+    fn apply_wind(&self, masses: &mut [Mass], wind: Vec3, wind_speed: Scalar) {
+        if wind_speed == 0.0 {
+            return;
+        }
+        if let Some(turbulence) = &self.turbulence {
+            // Sampled per mass, then averaged over each triangle's corners
+            let gusts: Vec<Vec3> = masses
+                .iter()
+                .map(|mass| turbulence.sample(mass.position, self.time))
+                .collect();
+            for triangle in self.triangles() {
+                let wind = centroid(&triangle.map(|index| gusts[index]));
+                apply_wind_to_triangle(masses, triangle, wind, wind_speed, self.wind_model);
+            }
+            return;
+        }
+
+        for triangle in self.triangles() {
+            apply_wind_to_triangle(masses, triangle, wind, wind_speed, self.wind_model);
+        }
+    }
+
+    pub fn compute_accelerations(
+        &self,
+        positions: &[Vec3],
+        velocities: &[Vec3],
+        wind: Vec3,
+        wind_speed: Scalar,
+    ) -> Vec<Vec3> {
+        self.accelerations_at(positions, velocities, wind, wind_speed, None)
+    }
+
+    fn accelerations_at(
+        &self,
+        positions: &[Vec3],
+        velocities: &[Vec3],
+        wind: Vec3,
+        wind_speed: Scalar,
+        external: Option<&dyn Fn(&Mass) -> Vec3>,
+    ) -> Vec<Vec3> {
+        let mut masses = self.masses.clone();
+        for ((mass, position), velocity) in masses.iter_mut().zip(positions).zip(velocities) {
+            mass.position = *position;
+            mass.velocity = *velocity;
+            mass.acceleration = Vec3::zero();
+        }
+
+        self.accumulate_forces(&mut masses, wind, wind_speed, external, None);
+        masses
+            .iter()
+            .map(|mass| mass.acceleration * mass.inv_mass)
+            .collect()
+    }
+
+    fn rk4_step(
+        &mut self,
+        dt: Scalar,
+        wind: Vec3,
+        wind_speed: Scalar,
+        external: Option<&dyn Fn(&Mass) -> Vec3>,
+    ) {
+        let x0: Vec<Vec3> = self.masses.iter().map(|mass| mass.position).collect();
+        let v0: Vec<Vec3> = self.masses.iter().map(|mass| mass.velocity).collect();
+        let offset = |base: &[Vec3], delta: &[Vec3], h: Scalar| -> Vec<Vec3> {
+            base.iter().zip(delta).map(|(b, d)| *b + *d * h).collect()
+        };
+
+        let k1x = v0.clone();
+        let k1v = self.accelerations_at(&x0, &v0, wind, wind_speed, external);
+        let k2x = offset(&v0, &k1v, 0.5 * dt);
+        let k2v = self.accelerations_at(
+            &offset(&x0, &k1x, 0.5 * dt),
+            &k2x,
+            wind,
+            wind_speed,
+            external,
+        );
+        let k3x = offset(&v0, &k2v, 0.5 * dt);
+        let k3v = self.accelerations_at(
+            &offset(&x0, &k2x, 0.5 * dt),
+            &k3x,
+            wind,
+            wind_speed,
+            external,
+        );
+        let k4x = offset(&v0, &k3v, dt);
+        let k4v = self.accelerations_at(&offset(&x0, &k3x, dt), &k4x, wind, wind_speed, external);
+
+        for (i, mass) in self.masses.iter_mut().enumerate() {
+            mass.acceleration = Vec3::zero();
+            mass.previous_position = mass.position;
+            let mut displacement = dt / 6.0 * (k1x[i] + 2.0 * k2x[i] + 2.0 * k3x[i] + k4x[i]);
+            mass.velocity += dt / 6.0 * (k1v[i] + 2.0 * k2v[i] + 2.0 * k3v[i] + k4v[i]);
+            if let Some(max_velocity) = self.max_velocity {
+                displacement = displacement.clamp_length(max_velocity * dt);
+                mass.velocity = mass.velocity.clamp_length(max_velocity);
+            }
+            mass.position += displacement;
+        }
+    }
+}
+
+impl Default for Cloth {
+    fn default() -> Self {
+        Cloth::new(DEFAULT_COLS, DEFAULT_ROWS, DEFAULT_SPACING, DEFAULT_STIFFNESS)
+    }
+}
+
+// Bending force from Bridson et al. 2003, "Simulation of Clothing with Folds and
+// Wrinkles", with a flat rest angle
+fn apply_dihedral_force(masses: &mut [Mass], hinge: [usize; 4], stiffness: Scalar) {
+    let [x1, x2, x3, x4] = hinge.map(|index| masses[index].position);
+    let edge = x4 - x3;
+    let edge_length = edge.length();
+    let n1 = (x1 - x3).cross(&(x1 - x4));
+    let n2 = (x2 - x4).cross(&(x2 - x3));
+    let (n1_squared, n2_squared) = (n1.length_squared(), n2.length_squared());
+    if edge_length == 0.0 || n1_squared == 0.0 || n2_squared == 0.0 {
+        return;
+    }
+
+    let (n1_hat, n2_hat) = (n1.normalize(), n2.normalize());
+    let edge_hat = edge / edge_length;
+    let half_angle_sin = ((1.0 - n1_hat.dot(&n2_hat)) / 2.0).max(0.0).sqrt();
+    let sin_half = half_angle_sin * n1_hat.cross(&n2_hat).dot(&edge_hat).signum();
+
+    let u1 = n1 * (edge_length / n1_squared);
+    let u2 = n2 * (edge_length / n2_squared);
+    let u3 = n1 * ((x1 - x4).dot(&edge_hat) / n1_squared)
+        + n2 * ((x2 - x4).dot(&edge_hat) / n2_squared);
+    let u4 = -(n1 * ((x1 - x3).dot(&edge_hat) / n1_squared)
+        + n2 * ((x2 - x3).dot(&edge_hat) / n2_squared));
+
+    let magnitude = stiffness * edge_length * edge_length / (n1_squared.sqrt() + n2_squared.sqrt())
+        * sin_half;
+    for (index, direction) in hinge.into_iter().zip([u1, u2, u3, u4]) {
+        masses[index].apply_force(direction * magnitude);
+    }
+}
+
+fn apply_wind_to_triangle(
+    masses: &mut [Mass],
+    triangle: [usize; 3],
+    wind: Vec3,
+    wind_speed: Scalar,
+    wind_model: WindModel,
+) {
+    let [a, b, c] = triangle;
+    let edge1 = masses[b].position - masses[a].position;
+    let edge2 = masses[c].position - masses[a].position;
+    let cross = edge1.cross(&edge2);
+    let area = 0.5 * cross.length();
+    let normal = cross.normalize();
+
+    let surface_velocity = (masses[a].velocity + masses[b].velocity + masses[c].velocity) / 3.0;
+    let wind_relative = wind * wind_speed - surface_velocity;
+    let projected = normal.dot(&wind_relative);
+    if wind_model == WindModel::OneSided && projected < 0.0 {
+        return;
+    }
+    let force = area * projected * normal;
+
+    for index in triangle {
+        masses[index].apply_force(force / 3.0);
+    }
+}
+
+struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        // xorshift gets stuck on an all-zero state
+        XorShift {
+            state: if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed },
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    fn next_signed(&mut self) -> Scalar {
+        let unit = (self.next_u64() >> 40) as Scalar / (1u64 << 24) as Scalar;
+        unit * 2.0 - 1.0
+    }
+}
+
+// Total force magnitude added to free masses since the last call, which then
+// becomes the new baseline
+fn force_delta(masses: &[Mass], before: &mut [Vec3]) -> Scalar {
+    let mut total = 0.0;
+    for (mass, before) in masses.iter().zip(before.iter_mut()) {
+        if !mass.is_pinned() {
+            total += (mass.acceleration - *before).length();
+        }
+        *before = mass.acceleration;
+    }
+    total
+}
+
+fn for_each_mass(masses: &mut [Mass], f: impl Fn(&mut Mass) + Sync + Send) {
+    #[cfg(feature = "rayon")]
+    masses.par_iter_mut().for_each(f);
+    #[cfg(not(feature = "rayon"))]
+    masses.iter_mut().for_each(f);
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mass {
+    pub position: Vec3,
+    pub previous_position: Vec3,
+    pub velocity: Vec3,
+    pub acceleration: Vec3,
+    pub normal: Vec3,
+    // Private so the two can't disagree; change them with set_mass and set_pinned
+    mass: Scalar,
+    // Zero for pinned masses, which forces, impulses and constraints leave alone
+    inv_mass: Scalar,
+}
+
+impl Mass {
+    pub fn new(position: Vec3, mass: Scalar, pinned: bool) -> Self {
+        assert!(mass > 0.0, "mass must be positive");
+        let mut result = Mass {
+            position,
+            previous_position: position,
+            velocity: Vec3::zero(),
+            acceleration: Vec3::zero(),
+            normal: Vec3::zero(),
+            mass,
+            inv_mass: 0.0,
+        };
+        result.set_pinned(pinned);
+        result
+    }
+
+    pub fn mass(&self) -> Scalar {
+        self.mass
+    }
+
+    pub fn inv_mass(&self) -> Scalar {
+        self.inv_mass
+    }
+
+    pub fn is_pinned(&self) -> bool {
+        self.inv_mass == 0.0
+    }
+
+    pub fn set_pinned(&mut self, pinned: bool) {
+        if pinned {
+            self.inv_mass = 0.0;
+            self.velocity = Vec3::zero();
+        } else {
+            self.inv_mass = 1.0 / self.mass;
+        }
+    }
+
+    pub fn set_mass(&mut self, mass: Scalar) {
+        assert!(mass > 0.0, "mass must be positive");
+        self.mass = mass;
+        if !self.is_pinned() {
+            self.inv_mass = 1.0 / mass;
+        }
+    }
+
+    pub fn apply_force(&mut self, force: Vec3) {
+        self.acceleration += force;
+    }
+
+    pub fn apply_impulse(&mut self, impulse: Vec3) {
+        self.velocity += impulse * self.inv_mass;
+    }
+
+    // Swaps the explicit damping force already accumulated for the damping at
+    // the end of the step, which can't overshoot however large damping * dt is
+    fn damp_implicitly(&mut self, damping: Scalar, dt: Scalar) {
+        if damping == 0.0 {
+            return;
+        }
+        let undamped = self.acceleration + damping * self.velocity;
+        let velocity = (self.velocity + undamped * self.inv_mass * dt)
+            / (1.0 + damping * self.inv_mass * dt);
+        self.acceleration = (velocity - self.velocity) * self.mass / dt;
+    }
+
+    // Pinned masses have no inverse mass, so forces never reach them. They are
+    // kinematic and move only by the velocity that grabs, anchors and animated
+    // pins give them. The velocity is capped before it moves the mass, so a
+    // clamped mass never travels further than max_velocity * dt.
+    pub fn update(&mut self, dt: Scalar, integrator: Integrator, max_velocity: Option<Scalar>) {
+        let new_acc = self.acceleration * self.inv_mass;
+        match integrator {
+            // RK4 needs forces from the whole cloth and is stepped by
+            // Cloth::update, so a lone mass falls back to Euler
+            Integrator::Euler | Integrator::Rk4 => {
+                self.previous_position = self.position;
+                self.velocity += new_acc * dt;
+                if let Some(max_velocity) = max_velocity {
+                    self.velocity = self.velocity.clamp_length(max_velocity);
+                }
+                self.position += self.velocity * dt;
+            }
+            Integrator::Verlet => {
+                // Equivalent to 2 * position - previous_position + a * dt^2 while
+                // letting impulses and collisions edit the velocity directly
+                let mut step = self.velocity * dt + new_acc * dt * dt;
+                if let Some(max_velocity) = max_velocity {
+                    step = step.clamp_length(max_velocity * dt);
+                }
+                let new_position = self.position + step;
+                self.previous_position = self.position;
+                self.position = new_position;
+                self.velocity = (self.position - self.previous_position) / dt;
+            }
+        }
+        self.acceleration = Vec3::zero();
+    }
+}
+
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Spring {
+    pub a: usize,
+    pub b: usize,
+    pub rest_length: Scalar,
+    pub stiffness: Scalar,
+    pub damping: Scalar,
+    pub max_strain: Option<Scalar>,
+}
+
+impl Spring {
+    pub fn new(a: usize, b: usize, rest_length: Scalar, stiffness: Scalar) -> Self {
+        Spring {
+            a,
+            b,
+            rest_length,
+            stiffness,
+            damping: 0.0,
+            max_strain: None,
+        }
+    }
+
+    pub fn strain(&self, masses: &[Mass]) -> Scalar {
+        let length = masses[self.a].position.distance(&masses[self.b].position);
+        (length - self.rest_length) / self.rest_length
+    }
+
+    pub fn is_torn(&self, masses: &[Mass]) -> bool {
+        match self.max_strain {
+            Some(max_strain) => self.strain(masses) > max_strain,
+            None => false,
+        }
+    }
+
+    pub fn potential_energy(&self, masses: &[Mass]) -> Scalar {
+        let length = masses[self.a].position.distance(&masses[self.b].position);
+        let extension = length - self.rest_length;
+        0.5 * self.stiffness * extension * extension
+    }
+
+    pub fn relax(&self, masses: &mut [Mass]) {
+        let weight_a = masses[self.a].inv_mass;
+        let weight_b = masses[self.b].inv_mass;
+        let total_weight = weight_a + weight_b;
+        if total_weight == 0.0 {
+            return;
+        }
+
+        let distance = masses[self.b].position - masses[self.a].position;
+        let length = distance.length();
+        if length == 0.0 {
+            return;
+        }
+
+        let correction = (length - self.rest_length) / (length * total_weight) * distance;
+        masses[self.a].position += weight_a * correction;
+        masses[self.b].position -= weight_b * correction;
+    }
+
+    pub fn force(&self, masses: &[Mass]) -> Vec3 {
+        let distance = masses[self.b].position - masses[self.a].position;
+        let length = masses[self.a].position.distance(&masses[self.b].position);
+        let direction = distance.normalize();
+        let relative_velocity = masses[self.b].velocity - masses[self.a].velocity;
+        self.stiffness * (length - self.rest_length) * direction
+            + self.damping * relative_velocity.dot(&direction) * direction
+    }
+
+    // Returns whether the spring was too long and had to be shortened
+    pub fn limit_stretch(&self, masses: &mut [Mass], max_stretch: Scalar) -> bool {
+        let weight_a = masses[self.a].inv_mass;
+        let weight_b = masses[self.b].inv_mass;
+        let total_weight = weight_a + weight_b;
+        if total_weight == 0.0 {
+            return false;
+        }
+
+        let distance = masses[self.b].position - masses[self.a].position;
+        let length = distance.length();
+        let max_length = self.rest_length * (1.0 + max_stretch);
+        if length <= max_length {
+            return false;
+        }
+
+        let correction = (length - max_length) / (length * total_weight) * distance;
+        masses[self.a].position += weight_a * correction;
+        masses[self.b].position -= weight_b * correction;
+        true
+    }
+
+    pub fn apply_force(&self, masses: &mut [Mass]) {
+        let force = self.force(masses);
+
+        masses[self.a].apply_force(force);
+        masses[self.b].apply_force(-force);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::defaults::DEFAULT_DT;
+
+    #[test]
+    fn verlet_settles_a_heavily_damped_spring_faster_than_euler() {
+        // Returns the step after which the spring stays within 1e-3 of its rest
+        // length, and the largest stretch seen
+        let settle = |integrator: Integrator| {
+            let mut cloth = Cloth::new(2, 1, 1.0, 100.0);
+            cloth.pin(0, 0);
+            cloth.set_gravity(Vec3::zero());
+            cloth.set_integrator(integrator);
+            cloth.set_damping(20.0);
+            cloth.masses[1].position = Vec3::new(1.5, 0.0, 0.0);
+            cloth.masses[1].previous_position = cloth.masses[1].position;
+
+            let (mut settled_at, mut largest) = (None, 0.0);
+            for step in 0..100 {
+                cloth.update(0.05, Vec3::zero(), 0.0);
+                let error = (cloth.masses[1].position.x - 1.0).abs();
+                largest = Scalar::max(largest, error);
+                if error >= 1e-3 {
+                    settled_at = None;
+                } else if settled_at.is_none() {
+                    settled_at = Some(step);
+                }
+            }
+            assert_eq!(cloth.masses[0].previous_position, cloth.masses[0].position);
+            (settled_at, largest)
+        };
+
+        // At damping * dt / mass = 2 explicit damping overshoots and Euler blows up
+        let (verlet, verlet_largest) = settle(Integrator::Verlet);
+        let (euler, euler_largest) = settle(Integrator::Euler);
+        assert!(verlet.is_some_and(|step| step < 20));
+        assert!(verlet_largest <= 0.5);
+        assert_eq!(euler, None);
+        assert!(euler_largest > 1.0);
+    }
+
+    #[test]
+    fn zero_gravity_leaves_a_free_mass_at_rest() {
+        let mut cloth = Cloth::new(1, 1, 1.0, 100.0);
+        cloth.set_gravity(Vec3::zero());
+        assert_eq!(cloth.gravity(), Vec3::zero());
+
+        for _ in 0..500 {
+            cloth.update(0.01, Vec3::zero(), 0.0);
+        }
+        assert_eq!(cloth.masses[0].position, Vec3::zero());
+    }
+
+    #[test]
+    fn overstretched_springs_tear_and_relaxed_ones_survive() {
+        let mut cloth = Cloth::new(4, 1, 1.0, 100.0);
+        cloth.pin(0, 0);
+        cloth.set_tear_threshold(0.5);
+        cloth.masses[3].position = Vec3::new(10.0, 0.0, 0.0);
+
+        let report = cloth.update(0.01, Vec3::zero(), 0.0);
+        assert!(report.torn_springs.contains(&(2, 3)));
+        assert!(cloth
+            .structural_springs
+            .iter()
+            .all(|spring| spring.a != 3 && spring.b != 3));
+        for pair in [(0, 1), (1, 2)] {
+            assert!(cloth
+                .structural_springs
+                .iter()
+                .any(|spring| (spring.a, spring.b) == pair));
+        }
+    }
+
+    #[test]
+    fn horizontal_wind_pushes_a_pinned_vertical_sheet_out_of_plane() {
+        let mut cloth = Cloth::new_oriented(5, 5, 1.0, 100.0, Orientation::Vertical);
+        cloth.apply_pin_pattern(PinPattern::TopEdge);
+        for _ in 0..200 {
+            cloth.update(0.01, Vec3::new(0.0, 0.0, 1.0), 5.0);
+        }
+
+        let deflection = cloth.masses.iter().map(|mass| mass.position.z).fold(0.0, Scalar::max);
+        assert!(deflection > 0.5, "the sheet only moved {deflection} out of plane");
+        assert!(cloth.masses.iter().all(|mass| mass.position.z > -1e-4));
+    }
+
+    #[test]
+    fn zero_wind_speed_adds_no_wind_force() {
+        // A sheet moving through still air would feel drag if the wind were applied
+        let mut cloth = Cloth::new_oriented(4, 4, 1.0, 100.0, Orientation::Vertical);
+        cloth.set_gravity(Vec3::zero());
+        cloth.set_damping(0.0);
+        for mass in &mut cloth.masses {
+            mass.velocity = Vec3::new(0.0, 0.0, 2.0);
+        }
+        cloth.update(0.01, Vec3::new(0.0, 0.0, 1.0), 0.0);
+
+        for mass in &cloth.masses {
+            assert_eq!(mass.velocity, Vec3::new(0.0, 0.0, 2.0));
+        }
+    }
+
+    #[test]
+    fn pin_and_unpin_round_trip() {
+        let mut cloth = Cloth::new(3, 3, 1.0, 100.0);
+        assert!(cloth.masses.iter().all(|mass| !mass.is_pinned()));
+
+        cloth.pin(1, 2);
+        assert!(cloth.mass_at(1, 2).unwrap().is_pinned());
+        assert_eq!(cloth.masses.iter().filter(|mass| mass.is_pinned()).count(), 1);
+
+        cloth.unpin(1, 2);
+        let mass = cloth.mass_at(1, 2).unwrap();
+        assert!(!mass.is_pinned());
+        assert_eq!(mass.inv_mass(), 1.0 / mass.mass());
+
+        cloth.pin(0, 0);
+        cloth.pin(2, 2);
+        cloth.unpin_all();
+        assert!(cloth.masses.iter().all(|mass| !mass.is_pinned()));
+    }
+
+    #[test]
+    fn pinning_out_of_range_is_ignored() {
+        let mut cloth = Cloth::new(3, 3, 1.0, 100.0);
+        cloth.pin(3, 0);
+        cloth.pin(0, 3);
+        cloth.pin(usize::MAX, usize::MAX);
+        cloth.unpin(5, 5);
+        assert!(cloth.masses.iter().all(|mass| !mass.is_pinned()));
+    }
+
+    #[test]
+    fn reset_restores_the_initial_grid() {
+        let mut cloth = Cloth::new(5, 5, 1.0, 100.0);
+        cloth.pin(0, 0);
+        let fresh = Cloth::new(5, 5, 1.0, 100.0);
+        for _ in 0..200 {
+            cloth.update(0.01, Vec3::new(1.0, 0.0, 0.5), 5.0);
+        }
+        let springs = cloth.structural_springs.len();
+
+        cloth.reset();
+        for (mass, expected) in cloth.masses.iter().zip(&fresh.masses) {
+            assert!(mass.position.approx_eq(&expected.position, 1e-6));
+            assert_eq!(mass.velocity, Vec3::zero());
+            assert_eq!(mass.acceleration, Vec3::zero());
+        }
+        assert!(cloth.mass_at(0, 0).unwrap().is_pinned());
+        assert_eq!(cloth.structural_springs.len(), springs);
+        assert_eq!(cloth.steps(), 0);
+    }
+
+    #[test]
+    fn rectangular_cloth_rest_lengths_follow_both_spacings() {
+        let cloth = Cloth::new_rect(3, 3, 1.0, 2.0, 100.0);
+        let rest_length = |springs: &[Spring], a: usize, b: usize| {
+            springs
+                .iter()
+                .find(|spring| (spring.a, spring.b) == (a, b) || (spring.a, spring.b) == (b, a))
+                .map(|spring| spring.rest_length)
+        };
+
+        // Index is row * cols + col, columns run along x and rows along z
+        assert_eq!(rest_length(&cloth.structural_springs, 0, 1), Some(1.0));
+        assert_eq!(rest_length(&cloth.structural_springs, 0, 3), Some(2.0));
+        let diagonal = rest_length(&cloth.shear_springs, 0, 4).unwrap();
+        assert!((diagonal - (1.0 as Scalar).hypot(2.0)).abs() < 1e-6);
+        assert_eq!(rest_length(&cloth.bend_springs, 0, 2), Some(2.0));
+        assert_eq!(rest_length(&cloth.bend_springs, 0, 6), Some(4.0));
+    }
+
+    #[test]
+    fn more_solver_iterations_reduce_stretch() {
+        let max_strain = |iterations: usize| {
+            let mut cloth = Cloth::new(8, 8, 0.5, 50.0);
+            cloth.apply_pin_pattern(PinPattern::TopEdge);
+            cloth.set_solver_iterations(iterations);
+            for _ in 0..300 {
+                cloth.update(0.01, Vec3::zero(), 0.0);
+            }
+            cloth
+                .structural_springs
+                .iter()
+                .map(|spring| spring.strain(&cloth.masses))
+                .fold(0.0, Scalar::max)
+        };
+
+        let strains: Vec<Scalar> = [1, 2, 4, 8].into_iter().map(max_strain).collect();
+        for pair in strains.windows(2) {
+            assert!(pair[1] < pair[0], "strain did not fall: {strains:?}");
+        }
+    }
+
+    #[test]
+    fn heavier_mass_accelerates_less_under_the_same_force() {
+        let mut light = Mass::new(Vec3::zero(), 0.5, false);
+        let mut heavy = Mass::new(Vec3::zero(), 2.0, false);
+        for mass in [&mut light, &mut heavy] {
+            mass.apply_force(Vec3::new(1.0, 0.0, 0.0));
+            mass.update(0.01, Integrator::Euler, None);
+        }
+
+        assert!(heavy.velocity.x < light.velocity.x);
+        assert!((light.velocity.x / heavy.velocity.x - 4.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn gravity_accelerates_every_mass_equally() {
+        let mut cloth = Cloth::new(2, 2, 1.0, 0.0);
+        cloth.set_damping(0.0);
+        cloth.set_mass(0, 0, 0.5);
+        cloth.set_mass(1, 1, 2.0);
+        cloth.update(0.01, Vec3::zero(), 0.0);
+
+        let light = cloth.masses[0].velocity.y;
+        let heavy = cloth.masses[3].velocity.y;
+        assert!((light - heavy).abs() < 1e-6);
+        assert!((light + 9.81 * 0.01).abs() < 1e-5);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trip_steps_identically() {
+        let mut cloth = Cloth::new(5, 5, 1.0, 100.0);
+        cloth.apply_pin_pattern(PinPattern::TopCorners);
+        for _ in 0..50 {
+            cloth.update(0.01, Vec3::new(1.0, 0.0, 0.5), 3.0);
+        }
+
+        let mut copy = Cloth::from_json(&cloth.to_json()).unwrap();
+        assert_eq!(copy.structural_springs.len(), cloth.structural_springs.len());
+        for (a, b) in copy.masses.iter().zip(&cloth.masses) {
+            assert_eq!(a.velocity, b.velocity);
+            assert_eq!(a.is_pinned(), b.is_pinned());
+        }
+
+        cloth.update(0.01, Vec3::new(1.0, 0.0, 0.5), 3.0);
+        copy.update(0.01, Vec3::new(1.0, 0.0, 0.5), 3.0);
+        assert_eq!(copy.positions(), cloth.positions());
+    }
+
+    #[test]
+    fn export_obj_writes_two_upward_faces_for_a_2x2_cloth() {
+        let obj = Cloth::new(2, 2, 1.0, 100.0).export_obj();
+        let vertices: Vec<Vec3> = obj
+            .lines()
+            .filter_map(|line| line.strip_prefix("v "))
+            .map(|line| {
+                let values: Vec<Scalar> =
+                    line.split_whitespace().map(|value| value.parse().unwrap()).collect();
+                Vec3::new(values[0], values[1], values[2])
+            })
+            .collect();
+        let faces: Vec<Vec<usize>> = obj
+            .lines()
+            .filter_map(|line| line.strip_prefix("f "))
+            .map(|line| line.split_whitespace().map(|index| index.parse().unwrap()).collect())
+            .collect();
+
+        assert_eq!(vertices.len(), 4);
+        assert_eq!(faces.len(), 2);
+        for face in &faces {
+            assert_eq!(face.len(), 3);
+            assert!(face.iter().all(|&index| (1..=4).contains(&index)));
+            let [a, b, c] = [face[0], face[1], face[2]].map(|index| vertices[index - 1]);
+            assert!((b - a).cross(&(c - a)).y > 0.0);
+        }
+    }
+
+    #[test]
+    fn soft_bending_lets_the_free_edge_droop_further() {
+        let tip_height = |bend: Scalar| {
+            let stiffness = SpringStiffness {
+                structural: 2000.0,
+                shear: 2000.0,
+                bend,
+            };
+            let mut cloth = Cloth::new(6, 6, 0.5, stiffness);
+            // Clamping the first two rows holds the sheet out flat like a cantilever
+            for col in 0..6 {
+                cloth.pin(0, col);
+                cloth.pin(1, col);
+            }
+            for _ in 0..1000 {
+                cloth.update(0.005, Vec3::zero(), 0.0);
+            }
+            cloth.masses[5 * 6 + 2].position.y
+        };
+
+        let uniform = tip_height(2000.0);
+        let soft = tip_height(20.0);
+        assert!(soft < uniform - 0.05, "soft tip at {soft}, uniform tip at {uniform}");
+    }
+
+    #[test]
+    fn spring_damping_reduces_oscillation() {
+        let oscillations = |damping: Scalar| {
+            let mut cloth = Cloth::new(2, 1, 1.0, 100.0);
+            cloth.set_gravity(Vec3::zero());
+            cloth.set_damping(0.0);
+            cloth.set_spring_damping(damping);
+            cloth.pin(0, 0);
+            cloth.masses[1].position = Vec3::new(1.5, 0.0, 0.0);
+
+            // Only count swings through rest length that still have visible amplitude
+            let mut crossings = 0;
+            let mut previous = cloth.structural_springs[0].strain(&cloth.masses);
+            let mut peak = previous.abs();
+            for _ in 0..1000 {
+                cloth.update(0.005, Vec3::zero(), 0.0);
+                let strain = cloth.structural_springs[0].strain(&cloth.masses);
+                if strain.signum() != previous.signum() {
+                    if peak > 0.005 {
+                        crossings += 1;
+                    }
+                    peak = 0.0;
+                }
+                peak = peak.max(strain.abs());
+                previous = strain;
+            }
+            crossings
+        };
+
+        let counts: Vec<usize> = [0.0, 4.0, 12.0].into_iter().map(oscillations).collect();
+        assert!(counts[0] > counts[1] && counts[1] > counts[2], "{counts:?}");
+    }
+
+    #[test]
+    fn verlet_without_damping_conserves_energy() {
+        let mut cloth = Cloth::new(5, 5, 1.0, 100.0);
+        cloth.apply_pin_pattern(PinPattern::TopCorners);
+        cloth.set_integrator(Integrator::Verlet);
+        cloth.set_damping(0.0);
+
+        // The energy starts at zero, so measure the drift against the energy
+        // that sloshes between kinetic and potential
+        let initial = cloth.kinetic_energy() + cloth.potential_energy();
+        let mut max_drift: Scalar = 0.0;
+        let mut max_kinetic: Scalar = 0.0;
+        for _ in 0..200 {
+            cloth.update(0.01, Vec3::zero(), 0.0);
+            let energy = cloth.kinetic_energy() + cloth.potential_energy();
+            max_drift = max_drift.max((energy - initial).abs());
+            max_kinetic = max_kinetic.max(cloth.kinetic_energy());
+        }
+
+        assert!(max_kinetic > 0.0);
+        assert!(max_drift < 0.05 * max_kinetic);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_spring_forces_match_the_sequential_sum() {
+        let mut cloth = Cloth::new(50, 50, 0.2, 100.0);
+        cloth.apply_pin_pattern(PinPattern::TopCorners);
+        cloth.perturb(0.05, 7);
+        for _ in 0..5 {
+            cloth.update(0.01, Vec3::new(1.0, 0.0, 0.5), 4.0);
+        }
+
+        let mut sequential = cloth.masses.clone();
+        for (spring, _) in cloth.all_springs() {
+            spring.apply_force(&mut sequential);
+        }
+
+        // Without a coloring the parallel forces are summed in spring order
+        let mut parallel = cloth.masses.clone();
+        cloth.apply_spring_forces(&mut parallel);
+        for (a, b) in parallel.iter().zip(&sequential) {
+            assert_eq!(a.acceleration, b.acceleration);
+        }
+
+        // A coloring changes the summation order, so only round-off differs
+        cloth.color_springs();
+        let mut colored = cloth.masses.clone();
+        cloth.apply_spring_forces(&mut colored);
+        for (a, b) in colored.iter().zip(&sequential) {
+            assert!(a.acceleration.approx_eq(&b.acceleration, 1e-3));
+        }
+    }
+
+    #[test]
+    fn self_collision_separates_a_folded_cloth() {
+        let mut cloth = Cloth::new(8, 3, 1.0, 50.0);
+        cloth.set_gravity(Vec3::zero());
+        cloth.set_self_collision(true, 0.5);
+        // Fold the right half over the left, just above it and closing in
+        for row in 0..3 {
+            for col in 4..8 {
+                let mass = &mut cloth.masses[row * 8 + col];
+                mass.position = Vec3::new(7.0 - col as Scalar, 0.3, row as Scalar);
+                mass.previous_position = mass.position;
+                mass.velocity = Vec3::new(0.0, -2.0, 0.0);
+            }
+        }
+        let adjacent: HashSet<(usize, usize)> = cloth
+            .structural_springs
+            .iter()
+            .chain(&cloth.shear_springs)
+            .map(|spring| (spring.a.min(spring.b), spring.a.max(spring.b)))
+            .collect();
+
+        for _ in 0..100 {
+            cloth.update(0.01, Vec3::zero(), 0.0);
+            for i in 0..cloth.masses.len() {
+                for j in i + 1..cloth.masses.len() {
+                    if !adjacent.contains(&(i, j)) {
+                        let distance = cloth.masses[i].position.distance(&cloth.masses[j].position);
+                        assert!(distance >= 0.5 - 1e-4, "{i} and {j} are {distance} apart");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn self_collision_ignores_spring_neighbours() {
+        let mut cloth = Cloth::new(3, 1, 0.5, 100.0);
+        cloth.set_gravity(Vec3::zero());
+        cloth.set_self_collision(true, 0.6);
+        cloth.update(0.01, Vec3::zero(), 0.0);
+
+        for (spring, _) in cloth.all_springs() {
+            assert!(spring.strain(&cloth.masses).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn flat_cloth_normals_point_up() {
+        let cloth = Cloth::new(4, 4, 1.0, 100.0);
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        assert!(cloth.face_normals().iter().all(|normal| normal.approx_eq(&up, 1e-6)));
+        assert!(cloth.vertex_normals().iter().all(|normal| normal.approx_eq(&up, 1e-6)));
+    }
+
+    #[test]
+    fn creased_cloth_has_differing_face_normals() {
+        let mut cloth = Cloth::new(3, 3, 1.0, 100.0);
+        for col in 0..3 {
+            cloth.masses[3 + col].position.y = 0.5;
+        }
+
+        let normals = cloth.face_normals();
+        assert!(normals.iter().any(|normal| normal.z > 0.1));
+        assert!(normals.iter().any(|normal| normal.z < -0.1));
+        assert!(normals.iter().all(|normal| normal.y > 0.0));
+        assert!((cloth.vertex_normals()[4].length() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn sinusoidal_wind_swings_the_cloth_both_ways() {
+        let mut cloth = Cloth::new_oriented(5, 5, 0.5, 100.0, Orientation::Vertical);
+        cloth.apply_pin_pattern(PinPattern::TopEdge);
+        cloth.set_wind_fn(|time| Vec3::new(0.0, 0.0, (TAU * time / 2.0).sin()));
+
+        let (mut lowest, mut highest) = (Scalar::MAX, Scalar::MIN);
+        for _ in 0..400 {
+            cloth.update(0.01, Vec3::zero(), 5.0);
+            let mean_z = cloth.masses.iter().map(|mass| mass.position.z).sum::<Scalar>() / 25.0;
+            lowest = lowest.min(mean_z);
+            highest = highest.max(mean_z);
+        }
+        assert!((cloth.time() - 4.0).abs() < 1e-3);
+        assert!(lowest < -0.01 && highest > 0.01, "z ranged over {lowest}..{highest}");
+    }
+
+    #[test]
+    fn impulses_change_velocity_but_not_pinned_masses() {
+        let mut cloth = Cloth::new(3, 3, 1.0, 100.0);
+        cloth.masses[4].set_mass(2.0);
+        cloth.pin(0, 0);
+
+        cloth.apply_impulse(4, Vec3::new(0.0, 4.0, 0.0));
+        assert_eq!(cloth.masses[4].velocity, Vec3::new(0.0, 2.0, 0.0));
+        cloth.apply_impulse(0, Vec3::new(0.0, 4.0, 0.0));
+        assert_eq!(cloth.masses[0].velocity, Vec3::zero());
+
+        cloth.apply_radial_impulse(cloth.masses[4].position, 1.0, 1.2);
+        assert_eq!(cloth.masses[0].velocity, Vec3::zero());
+        assert!(cloth.masses[1].velocity.z < 0.0);
+        assert!(cloth.masses[5].velocity.x > 0.0);
+        assert_eq!(cloth.masses[8].velocity, Vec3::zero());
+    }
+
+    #[test]
+    fn grabbed_mass_tracks_the_target_until_released() {
+        let mut cloth = Cloth::new(3, 3, 1.0, 100.0);
+        cloth.apply_pin_pattern(PinPattern::TopEdge);
+        let target = Vec3::new(1.0, 0.5, 2.5);
+        cloth.grab(7);
+        cloth.drag_to(target);
+        for _ in 0..50 {
+            cloth.update(0.01, Vec3::zero(), 0.0);
+            assert!(cloth.masses[7].position.approx_eq(&target, 1e-4));
+        }
+
+        cloth.release();
+        assert!(!cloth.masses[7].is_pinned());
+        for _ in 0..50 {
+            cloth.update(0.01, Vec3::zero(), 0.0);
+        }
+        assert!(cloth.masses[7].position.y < target.y - 0.1);
+    }
+
+    #[test]
+    fn index_and_coords_round_trip() {
+        let cloth = Cloth::new(4, 3, 1.0, 100.0);
+        for row in 0..3 {
+            for col in 0..4 {
+                let index = cloth.index(row, col).unwrap();
+                assert_eq!(cloth.coords(index), Some((row, col)));
+                assert_eq!(cloth.mass_at(row, col).unwrap().position, cloth.masses[index].position);
+            }
+        }
+
+        assert_eq!(cloth.index(3, 0), None);
+        assert_eq!(cloth.index(0, 4), None);
+        assert!(cloth.mass_at(3, 4).is_none());
+        assert_eq!(cloth.coords(12), None);
+        assert_eq!(Cloth::new(0, 3, 1.0, 100.0).coords(0), None);
+    }
+
+    #[test]
+    fn getters_match_the_constructor() {
+        let cloth = Cloth::new(4, 3, 1.0, 100.0);
+
+        assert_eq!(cloth.cols(), 4);
+        assert_eq!(cloth.rows(), 3);
+        // Every spring is listed once from each end
+        assert_eq!(cloth.shear_springs().len(), 2 * 2 * 3 * 2);
+        assert_eq!(cloth.bend_springs().len(), 2 * (2 * 3 + 4));
+        assert!(cloth
+            .shear_springs()
+            .iter()
+            .all(|spring| (spring.rest_length - Scalar::sqrt(2.0)).abs() < 1e-6));
+        assert!(cloth.bend_springs().iter().all(|spring| spring.rest_length == 2.0));
+    }
+
+    #[test]
+    fn rk4_tracks_a_damped_spring_closer_than_euler() {
+        let spring_pair = |integrator: Integrator| {
+            let mut cloth = Cloth::new(2, 1, 1.0, 100.0);
+            cloth.set_gravity(Vec3::zero());
+            cloth.set_integrator(integrator);
+            cloth.pin(0, 0);
+            cloth
+        };
+
+        // The pair is joined by a spring from each end, so their stiffness adds up
+        let stiffness: Scalar = spring_pair(Integrator::Euler)
+            .structural_springs
+            .iter()
+            .map(|spring| spring.stiffness)
+            .sum();
+        let (mass, damping, amplitude) = (DEFAULT_MASS, DEFAULT_DAMPING, 0.2);
+        let decay = damping / (2.0 * mass);
+        let frequency = (stiffness / mass - decay * decay).sqrt();
+        let analytic = |time: Scalar| {
+            let envelope = amplitude * (-decay * time).exp();
+            envelope * ((frequency * time).cos() + decay / frequency * (frequency * time).sin())
+        };
+
+        let max_error = |integrator: Integrator| {
+            let mut cloth = spring_pair(integrator);
+            cloth.masses[1].position.x += amplitude;
+
+            let mut error: Scalar = 0.0;
+            for _ in 0..200 {
+                cloth.update(0.01, Vec3::zero(), 0.0);
+                let extension = cloth.masses[1].position.x - 1.0;
+                error = error.max((extension - analytic(cloth.time())).abs());
+            }
+            error
+        };
+
+        let euler = max_error(Integrator::Euler);
+        let rk4 = max_error(Integrator::Rk4);
+        assert!(rk4 < 1e-3, "rk4 error {rk4}");
+        assert!(rk4 * 10.0 < euler, "rk4 error {rk4}, euler error {euler}");
+    }
+
+    #[test]
+    fn max_stretch_clamps_structural_springs() {
+        let mut cloth = Cloth::new(6, 6, 1.0, 20.0);
+        cloth.apply_pin_pattern(PinPattern::TopCorners);
+        cloth.set_max_stretch(0.1);
+        for _ in 0..200 {
+            cloth.update(0.01, Vec3::zero(), 0.0);
+            for spring in &cloth.structural_springs {
+                assert!(spring.strain(&cloth.masses) <= 0.1 + 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn max_stretch_leaves_springs_between_pins_alone() {
+        let mut cloth = Cloth::new(2, 1, 1.0, 100.0);
+        cloth.pin(0, 0);
+        cloth.pin(0, 1);
+        cloth.masses[1].position = Vec3::new(3.0, 0.0, 0.0);
+        cloth.set_max_stretch(0.1);
+        cloth.update(0.01, Vec3::zero(), 0.0);
+        assert_eq!(cloth.masses[0].position, Vec3::zero());
+        assert_eq!(cloth.masses[1].position, Vec3::new(3.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn perturbation_is_seeded_and_spares_pinned_masses() {
+        let perturbed = |seed: u64| {
+            let mut cloth = Cloth::new(5, 5, 1.0, 100.0);
+            cloth.apply_pin_pattern(PinPattern::TopCorners);
+            cloth.perturb(0.1, seed);
+            cloth
+        };
+
+        let cloth = perturbed(42);
+        assert_eq!(cloth.positions(), perturbed(42).positions());
+        assert_ne!(cloth.positions(), perturbed(43).positions());
+
+        let flat = Cloth::new(5, 5, 1.0, 100.0);
+        for (mass, original) in cloth.masses.iter().zip(&flat.masses) {
+            let offset = mass.position - original.position;
+            if mass.is_pinned() {
+                assert_eq!(offset, Vec3::zero());
+            } else {
+                assert!(offset != Vec3::zero() && offset.x.abs() <= 0.1);
+            }
+        }
+    }
+
+    #[test]
+    fn bounds_and_center_of_a_known_grid() {
+        let cloth = Cloth::new_rect(5, 3, 1.0, 2.0, 100.0);
+        let (min, max) = cloth.bounds();
+        assert_eq!(min, Vec3::zero());
+        assert_eq!(max, Vec3::new(4.0, 0.0, 4.0));
+        assert_eq!(cloth.center(), Vec3::new(2.0, 0.0, 2.0));
+    }
+
+    #[test]
+    fn cylinder_seam_joins_the_first_and_last_columns() {
+        let cloth = Cloth::new_cylinder(8, 3, 1.0, 0.5, 100.0);
+        let joined = |springs: &[Spring], a: usize, b: usize| {
+            springs
+                .iter()
+                .any(|spring| (spring.a, spring.b) == (a, b) || (spring.a, spring.b) == (b, a))
+        };
+
+        for row in 0..3 {
+            let (first, last) = (cloth.index(row, 0).unwrap(), cloth.index(row, 7).unwrap());
+            assert!(joined(&cloth.structural_springs, first, last));
+            assert!(joined(&cloth.bend_springs, first, cloth.index(row, 6).unwrap()));
+        }
+        assert!(joined(&cloth.shear_springs, 0, cloth.index(1, 7).unwrap()));
+
+        for mass in &cloth.masses {
+            let radius = Vec3::new(mass.position.x, 0.0, mass.position.z).length();
+            assert!((radius - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn steps_and_time_count_updates() {
+        let mut cloth = Cloth::new(3, 3, 1.0, 100.0);
+        for _ in 0..25 {
+            cloth.update(0.02, Vec3::zero(), 0.0);
+        }
+        assert_eq!(cloth.steps(), 25);
+        assert!((cloth.time() - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn enormous_force_leaves_no_nan() {
+        let mut cloth = Cloth::new(4, 4, 1.0, 100.0);
+        cloth.update_with(0.01, Vec3::zero(), 0.0, |mass| {
+            if mass.position == Vec3::zero() {
+                Vec3::new(Scalar::MAX, Scalar::MAX, 0.0)
+            } else {
+                Vec3::zero()
+            }
+        });
+        for _ in 0..10 {
+            cloth.update(0.01, Vec3::zero(), 0.0);
+        }
+
+        assert!(cloth.masses.iter().all(|mass| mass.position.is_finite()));
+        assert!(cloth.masses.iter().all(|mass| mass.velocity.is_finite()));
+        assert!(cloth.instability_count() > 0);
+    }
+
+    #[test]
+    fn max_velocity_survives_solver_iterations() {
+        for integrator in [Integrator::Euler, Integrator::Verlet, Integrator::Rk4] {
+            let build = |max_velocity| {
+                let mut cloth = Cloth::new(5, 5, 1.0, 100.0);
+                cloth.apply_pin_pattern(PinPattern::TopCorners);
+                cloth.set_integrator(integrator);
+                cloth.set_solver_iterations(4);
+                cloth.set_max_velocity(max_velocity);
+                cloth
+            };
+            let mut capped = build(Some(0.5));
+            let mut free = build(None);
+
+            let mut fastest_free: Scalar = 0.0;
+            for _ in 0..100 {
+                capped.update(0.01, Vec3::zero(), 0.0);
+                free.update(0.01, Vec3::zero(), 0.0);
+                for mass in &capped.masses {
+                    assert!(mass.velocity.length() <= 0.5 + 1e-4, "{integrator:?}");
+                }
+                for mass in &free.masses {
+                    fastest_free = fastest_free.max(mass.velocity.length());
+                }
+            }
+            assert!(fastest_free > 1.0);
+        }
+    }
+
+    #[test]
+    fn baked_rest_lengths_leave_no_spring_force() {
+        let mut cloth = Cloth::new(5, 5, 1.0, 100.0);
+        cloth.perturb(0.2, 3);
+        cloth.bake_rest_lengths();
+
+        for (spring, _) in cloth.all_springs() {
+            assert!(spring.strain(&cloth.masses).abs() < 1e-6);
+        }
+        let mut masses = cloth.masses.clone();
+        cloth.apply_spring_forces(&mut masses);
+        for mass in &masses {
+            assert!(mass.acceleration.approx_eq(&Vec3::zero(), 1e-4));
+        }
+    }
+
+    #[test]
+    fn quadratic_drag_reaches_terminal_velocity() {
+        let mut cloth = Cloth::new(1, 1, 1.0, 100.0);
+        cloth.set_damping(0.0);
+        cloth.set_air_drag(0.5);
+        let terminal = (DEFAULT_MASS * 9.81 / 0.5).sqrt();
+
+        let mut speeds = Vec::new();
+        for _ in 0..1000 {
+            cloth.update(0.01, Vec3::zero(), 0.0);
+            speeds.push(cloth.masses[0].velocity.length());
+        }
+        for pair in speeds.windows(2) {
+            assert!(pair[1] >= pair[0] - 1e-6 && pair[1] <= terminal + 1e-3);
+        }
+        assert!((speeds[999] - terminal).abs() < 0.01 * terminal);
+    }
+
+    #[test]
+    fn triangles_cover_every_quad_with_valid_indices() {
+        let cloth = Cloth::new(5, 4, 1.0, 100.0);
+        let triangles: Vec<[usize; 3]> = cloth.triangles().collect();
+        assert_eq!(triangles.len(), 2 * 4 * 3);
+        assert!(triangles.iter().flatten().all(|&index| index < cloth.masses.len()));
+    }
+
+    #[test]
+    fn resolutions_of_the_same_size_share_bounds() {
+        let coarse = Cloth::with_resolution(5, 4, 3.0, 2.0, 100.0);
+        let fine = Cloth::with_resolution(31, 17, 3.0, 2.0, 100.0);
+        let (min, max) = fine.bounds();
+        assert_eq!(coarse.bounds(), (min, max));
+        assert!(max.approx_eq(&Vec3::new(3.0, 0.0, 2.0), 1e-5));
+        assert_eq!(fine.masses.len(), 31 * 17);
+    }
+
+    #[test]
+    fn default_cloth_is_a_stable_20x20_sheet() {
+        let mut cloth = Cloth::default();
+        assert_eq!((cloth.cols(), cloth.rows()), (DEFAULT_COLS, DEFAULT_ROWS));
+        assert_eq!(cloth.masses.len(), DEFAULT_COLS * DEFAULT_ROWS);
+
+        for _ in 0..10 {
+            cloth.update(DEFAULT_DT, Vec3::zero(), 0.0);
+        }
+        for mass in &cloth.masses {
+            let p = mass.position;
+            assert!(p.x.is_finite() && p.y.is_finite() && p.z.is_finite());
+            assert!(p.y < 0.0);
+        }
+        for spring in &cloth.structural_springs {
+            let length = cloth.masses[spring.a].position.distance(&cloth.masses[spring.b].position);
+            assert!((length - DEFAULT_SPACING).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn step_report_names_exactly_the_torn_pair() {
+        let mut cloth = Cloth::new(3, 1, 1.0, 100.0);
+        cloth.set_gravity(Vec3::zero());
+        cloth.set_tear_threshold(1.0);
+        // Far enough to tear the 1-2 spring but not the 0-2 bend spring
+        cloth.masses[2].position = Vec3::new(2.0, 3.0, 0.0);
+
+        let report = cloth.update(0.01, Vec3::zero(), 0.0);
+        assert_eq!(report.torn_springs, vec![(1, 2)]);
+        assert_eq!(report.collisions, 0);
+        assert!(cloth.update(0.01, Vec3::zero(), 0.0).torn_springs.is_empty());
+    }
+
+    #[test]
+    fn two_sided_wind_pushes_a_triangle_facing_away() {
+        let push = |wind: Vec3, model: WindModel| {
+            let mut masses = vec![
+                Mass::new(Vec3::zero(), 1.0, false),
+                Mass::new(Vec3::new(0.0, 0.0, 1.0), 1.0, false),
+                Mass::new(Vec3::new(1.0, 0.0, 0.0), 1.0, false),
+            ];
+            apply_wind_to_triangle(&mut masses, [0, 1, 2], wind, 2.0, model);
+            masses.iter().fold(Vec3::zero(), |total, mass| total + mass.acceleration)
+        };
+
+        // The triangle's normal points up, and one-sided wind only catches wind
+        // blowing along the normal, so upward wind hits the front
+        let front = push(Vec3::new(0.0, 1.0, 0.0), WindModel::TwoSided);
+        let back = push(Vec3::new(0.0, -1.0, 0.0), WindModel::TwoSided);
+        assert!(front.y > 0.0);
+        assert_eq!(back, -front);
+
+        assert_eq!(push(Vec3::new(0.0, 1.0, 0.0), WindModel::OneSided), front);
+        assert_eq!(push(Vec3::new(0.0, -1.0, 0.0), WindModel::OneSided), Vec3::zero());
+    }
+
+    #[test]
+    fn boundary_of_a_4x4_cloth_runs_clockwise_from_zero() {
+        let cloth = Cloth::new(4, 4, 1.0, 100.0);
+        assert_eq!(
+            cloth.boundary_indices(),
+            vec![0, 1, 2, 3, 7, 11, 15, 14, 13, 12, 8, 4]
+        );
+
+        let springs = cloth.boundary_springs();
+        assert_eq!(springs.len(), 12);
+        assert_eq!(springs[0], (0, 1));
+        assert_eq!(springs[11], (4, 0));
+    }
+
+    fn swept_sphere_depth(substeps: usize) -> Scalar {
+        let mut cloth = Cloth::new(9, 9, 0.5, 200.0);
+        cloth.apply_pin_pattern(PinPattern::AllCorners);
+        cloth.set_gravity(Vec3::zero());
+        cloth.set_substeps(substeps);
+        cloth.add_collider(Collider::Sphere {
+            center: Vec3::new(2.0, 1.0, 2.0),
+            radius: 0.6,
+            friction: 0.0,
+            restitution: 0.0,
+            mask: u32::MAX,
+        });
+        // Far more than the sphere's diameter in a single update
+        cloth.move_collider(0, Vec3::new(0.0, -4.0, 0.0));
+        cloth.update(0.02, Vec3::zero(), 0.0);
+        cloth.mass_at(4, 4).unwrap().position.y
+    }
+
+    #[test]
+    fn substeps_stop_a_fast_sphere_tunnelling_through_the_cloth() {
+        assert_eq!(swept_sphere_depth(1), 0.0);
+        assert!(swept_sphere_depth(16) < -3.0);
+    }
+
+    #[test]
+    fn translate_shifts_bounds_and_scale_doubles_them() {
+        let mut cloth = Cloth::new_rect(4, 3, 1.0, 2.0, 100.0);
+        let (min, max) = cloth.bounds();
+        let offset = Vec3::new(1.0, -2.0, 0.5);
+        cloth.translate(offset);
+        assert_eq!(cloth.bounds(), (min + offset, max + offset));
+
+        let center = cloth.center();
+        cloth.scale(2.0);
+        let (scaled_min, scaled_max) = cloth.bounds();
+        assert!((scaled_max - scaled_min).approx_eq(&((max - min) * 2.0), 1e-5));
+        assert!(cloth.center().approx_eq(&center, 1e-5));
+        assert_eq!(cloth.structural_springs[0].rest_length, 2.0);
+
+        cloth.reset();
+        assert!(cloth.bounds().1.approx_eq(&scaled_max, 1e-5));
+    }
+
+    #[test]
+    fn pin_patterns_pin_exactly_their_masses() {
+        let pinned = |pattern: PinPattern| {
+            let mut cloth = Cloth::new(4, 3, 1.0, 100.0);
+            cloth.pin(1, 1);
+            cloth.apply_pin_pattern(pattern);
+            (0..cloth.masses.len())
+                .filter(|&index| cloth.masses[index].is_pinned())
+                .collect::<Vec<usize>>()
+        };
+
+        assert_eq!(pinned(PinPattern::None), Vec::<usize>::new());
+        assert_eq!(pinned(PinPattern::TopCorners), vec![0, 3]);
+        assert_eq!(pinned(PinPattern::TopEdge), vec![0, 1, 2, 3]);
+        assert_eq!(pinned(PinPattern::LeftEdge), vec![0, 4, 8]);
+        assert_eq!(pinned(PinPattern::AllCorners), vec![0, 3, 8, 11]);
+    }
+
+    #[test]
+    fn zero_inverse_mass_freezes_a_mass() {
+        for integrator in [Integrator::Euler, Integrator::Verlet, Integrator::Rk4] {
+            let mut cloth = Cloth::new(3, 3, 1.0, 100.0);
+            cloth.set_integrator(integrator);
+            cloth.pin(1, 1);
+            let frozen = cloth.mass_at(1, 1).unwrap();
+            assert_eq!(frozen.inv_mass(), 0.0);
+            let start = frozen.position;
+
+            cloth.apply_impulse(cloth.index(1, 1).unwrap(), Vec3::new(5.0, 0.0, 0.0));
+            for _ in 0..50 {
+                cloth.update(0.01, Vec3::new(1.0, 0.0, 0.0), 10.0);
+            }
+            assert_eq!(cloth.mass_at(1, 1).unwrap().position, start);
+        }
+    }
+
+    #[test]
+    fn relaxation_moves_heavy_masses_less() {
+        let mut masses = vec![
+            Mass::new(Vec3::zero(), 1.0, false),
+            Mass::new(Vec3::new(2.0, 0.0, 0.0), 3.0, false),
+        ];
+        Spring::new(0, 1, 1.0, 100.0).relax(&mut masses);
+
+        assert!((masses[0].position.x - 0.75).abs() < 1e-6);
+        assert!((masses[1].position.x - 1.75).abs() < 1e-6);
+    }
+
+    #[test]
+    #[should_panic(expected = "mass must be positive")]
+    fn set_mass_rejects_non_positive_mass() {
+        Mass::new(Vec3::zero(), 1.0, false).set_mass(0.0);
+    }
+
+    #[test]
+    fn strain_is_relative_extension() {
+        let spring = Spring::new(0, 1, 2.0, 100.0);
+        let strain_at = |x: Scalar| {
+            let masses = [
+                Mass::new(Vec3::zero(), 1.0, false),
+                Mass::new(Vec3::new(x, 0.0, 0.0), 1.0, false),
+            ];
+            spring.strain(&masses)
+        };
+
+        assert_eq!(strain_at(2.0), 0.0);
+        assert_eq!(strain_at(4.0), 1.0);
+        assert_eq!(strain_at(1.0), -0.5);
+    }
+
+    #[test]
+    fn trace_csv_has_a_row_per_step() {
+        let mut cloth = Cloth::new(3, 3, 1.0, 100.0);
+        cloth.enable_trace(4);
+        for _ in 0..20 {
+            cloth.update(0.01, Vec3::zero(), 0.0);
+        }
+
+        let csv = cloth.trace_csv();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("time,x,y,z"));
+        let times: Vec<Scalar> = lines
+            .map(|line| {
+                let fields: Vec<&str> = line.split(',').collect();
+                assert_eq!(fields.len(), 4);
+                fields[0].parse().unwrap()
+            })
+            .collect();
+        assert_eq!(times.len(), 20);
+        assert!(times.windows(2).all(|pair| pair[1] > pair[0]));
+    }
+
+    #[test]
+    fn stitch_sews_matching_edge_masses() {
+        let mut left = Cloth::new(3, 3, 1.0, 100.0);
+        let mut right = Cloth::new(3, 3, 1.0, 100.0);
+        right.translate(Vec3::new(3.0, 0.0, 0.0));
+        let springs = left.structural_springs.len() + right.structural_springs.len();
+
+        left.stitch(&right, Edge::Right, Edge::Left).unwrap();
+        assert_eq!(left.masses.len(), 18);
+        let seam = &left.structural_springs[springs..];
+        let pairs: Vec<(usize, usize)> = seam.iter().map(|spring| (spring.a, spring.b)).collect();
+        assert_eq!(pairs, vec![(2, 9), (5, 12), (8, 15)]);
+        assert!(seam.iter().all(|spring| spring.rest_length == 1.0));
+        assert_eq!(left.bounds().1, Vec3::new(5.0, 0.0, 2.0));
+    }
+
+    #[test]
+    fn stitch_rejects_edges_of_different_lengths() {
+        let mut wide = Cloth::new(4, 3, 1.0, 100.0);
+        let narrow = Cloth::new(3, 3, 1.0, 100.0);
+        let springs = wide.structural_springs.len();
+
+        let result = wide.stitch(&narrow, Edge::Top, Edge::Top);
+        assert_eq!(
+            result,
+            Err(StitchError {
+                self_edge_len: 4,
+                other_edge_len: 3,
+            })
+        );
+        assert_eq!(wide.masses.len(), 12);
+        assert_eq!(wide.structural_springs.len(), springs);
+    }
+
+    #[test]
+    fn positive_pressure_inflates_a_cylinder() {
+        let inflated = |pressure: bool| {
+            let mut cloth = Cloth::new_cylinder(12, 6, 1.0, 0.5, 20.0);
+            cloth.set_gravity(Vec3::zero());
+            if pressure {
+                let volume = cloth.volume();
+                cloth.set_pressure(50.0, 2.0 * volume);
+            }
+            for _ in 0..200 {
+                cloth.update(0.01, Vec3::zero(), 0.0);
+            }
+            let (min, max) = cloth.bounds();
+            (cloth.volume(), (max.x - min.x) * (max.z - min.z))
+        };
+
+        let (plain_volume, plain_extent) = inflated(false);
+        let (volume, extent) = inflated(true);
+        assert!(volume > plain_volume * 1.1, "{volume} vs {plain_volume}");
+        assert!(extent > plain_extent * 1.1, "{extent} vs {plain_extent}");
+    }
+
+    #[test]
+    fn dihedral_bending_ignores_in_plane_stretch() {
+        let bend_force = |model: BendModel| {
+            let mut cloth = Cloth::new(4, 4, 1.0, 100.0);
+            cloth.set_bend_model(model);
+            for mass in &mut cloth.masses {
+                mass.position *= 1.5;
+            }
+            let mut masses = cloth.masses.clone();
+            cloth.apply_dihedral_forces(&mut masses);
+            for spring in cloth.active_bend_springs() {
+                spring.apply_force(&mut masses);
+            }
+            masses.iter().map(|mass| mass.acceleration.length()).fold(0.0, Scalar::max)
+        };
+
+        assert_eq!(bend_force(BendModel::Dihedral), 0.0);
+        assert!(bend_force(BendModel::DistanceSpring) > 1.0);
+    }
+
+    #[test]
+    fn all_springs_tags_each_list() {
+        let cloth = Cloth::new(4, 4, 1.0, 100.0);
+        let count = |kind: SpringKind| cloth.all_springs().filter(|(_, k)| *k == kind).count();
+        assert_eq!(count(SpringKind::Structural), cloth.structural_springs.len());
+        assert_eq!(count(SpringKind::Shear), cloth.shear_springs.len());
+        assert_eq!(count(SpringKind::Bend), cloth.bend_springs.len());
+        assert_eq!(
+            cloth.all_springs().count(),
+            cloth.structural_springs.len() + cloth.shear_springs.len() + cloth.bend_springs.len()
+        );
+    }
+
+    #[test]
+    fn nearest_mass_picks_the_closest_grid_point() {
+        let cloth = Cloth::new(4, 3, 1.0, 100.0);
+        assert_eq!(cloth.nearest_mass(Vec3::new(-0.5, 0.3, -0.2)), 0);
+        assert_eq!(cloth.nearest_mass(Vec3::new(2.1, 0.0, 0.9)), 6);
+        assert_eq!(cloth.nearest_mass(Vec3::new(2.9, -1.0, 2.2)), 11);
+        assert_eq!(cloth.nearest_mass(Vec3::new(0.8, 0.0, 2.4)), 9);
+    }
+
+    #[test]
+    fn vertical_cloth_hangs_in_the_xy_plane() {
+        let cloth = Cloth::new_oriented(3, 4, 0.5, 100.0, Orientation::Vertical);
+        for row in 0..4 {
+            for col in 0..3 {
+                let position = cloth.mass_at(row, col).unwrap().position;
+                assert_eq!(position, Vec3::new(col as Scalar * 0.5, -(row as Scalar) * 0.5, 0.0));
+            }
+        }
+        let horizontal = Cloth::new_oriented(3, 4, 0.5, 100.0, Orientation::Horizontal);
+        assert_eq!(horizontal.positions(), Cloth::new(3, 4, 0.5, 100.0).positions());
+        assert_eq!(horizontal.structural_springs.len(), cloth.structural_springs.len());
+    }
+
+    #[test]
+    fn strain_stats_find_the_stretched_spring() {
+        let mut cloth = Cloth::new(4, 1, 1.0, 100.0);
+        cloth.masses[3].position.x = 3.5;
+        let stats = cloth.strain_stats();
+
+        assert_eq!(stats.max, 0.5);
+        let spring = cloth.structural_springs[stats.max_index];
+        assert_eq!((spring.a.min(spring.b), spring.a.max(spring.b)), (2, 3));
+        // Two of the six structural springs join masses 2 and 3
+        assert!((stats.mean - 1.0 / 6.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn hard_yank_subdivides_and_gentle_motion_does_not() {
+        let mut cloth = Cloth::new(6, 6, 0.5, 100.0);
+        cloth.apply_pin_pattern(PinPattern::TopEdge);
+        cloth.set_adaptive(0.2, 1e-4);
+        for _ in 0..50 {
+            cloth.update(0.005, Vec3::zero(), 0.0);
+        }
+        assert_eq!(cloth.subdivisions(), 0);
+
+        cloth.apply_impulse(33, Vec3::new(0.0, -50.0, 0.0));
+        cloth.update(0.005, Vec3::zero(), 0.0);
+        assert!(cloth.subdivisions() > 0);
+    }
+
+    #[test]
+    fn turbulent_wind_varies_in_space_and_is_deterministic() {
+        let mut cloth = Cloth::new(3, 3, 1.0, 100.0);
+        cloth.set_turbulent_wind(Vec3::new(1.0, 0.0, 0.0), 2.0, 3.0);
+
+        let near = cloth.wind_at(Vec3::zero()).unwrap();
+        let far = cloth.wind_at(Vec3::new(50.0, 10.0, 30.0)).unwrap();
+        assert!(!near.approx_eq(&far, 1e-3));
+        assert_eq!(cloth.wind_at(Vec3::zero()).unwrap(), near);
+    }
+
+    #[test]
+    fn turbulent_wind_scales_with_wind_speed() {
+        let wind_total = |wind_speed: Scalar| {
+            let mut cloth = Cloth::new_oriented(4, 4, 1.0, 100.0, Orientation::Vertical);
+            cloth.set_turbulent_wind(Vec3::new(0.0, 0.0, 1.0), 0.5, 3.0);
+            cloth.enable_force_report();
+            cloth.update(0.01, Vec3::zero(), wind_speed);
+            cloth.last_force_report().unwrap().wind
+        };
+
+        assert_eq!(wind_total(0.0), 0.0);
+        assert!(wind_total(10.0) > 5.0 * wind_total(1.0));
+    }
+
+    #[test]
+    fn remove_mass_drops_only_its_springs() {
+        let mut cloth = Cloth::new(3, 3, 1.0, 100.0);
+        let before = cloth.all_springs().count();
+        let touching = cloth
+            .all_springs()
+            .filter(|(spring, _)| spring.a == 4 || spring.b == 4)
+            .count();
+        cloth.remove_mass(4);
+
+        assert_eq!(cloth.masses.len(), 8);
+        assert_eq!(cloth.all_springs().count(), before - touching);
+        for (spring, _) in cloth.all_springs() {
+            assert!(spring.a < 8 && spring.b < 8);
+        }
+        for triangle in cloth.triangles() {
+            assert!(triangle.iter().all(|&index| index < 8));
+        }
+    }
+
+    #[test]
+    fn grid_lookups_find_nothing_after_remove_mass() {
+        let mut cloth = Cloth::new(3, 3, 1.0, 100.0);
+        cloth.remove_mass(4);
+        cloth.apply_pin_pattern(PinPattern::AllCorners);
+        cloth.set_mass(2, 2, 2.0);
+
+        assert_eq!(cloth.index(2, 2), None);
+        assert!(cloth.boundary_indices().is_empty());
+        assert!(cloth.boundary_springs().is_empty());
+        assert!(cloth.edge_indices(Edge::Bottom).is_empty());
+        cloth.update(0.01, Vec3::zero(), 0.0);
+    }
+
+    #[test]
+    fn coords_are_gone_once_a_mass_is_removed() {
+        let mut cloth = Cloth::new(3, 3, 1.0, 100.0);
+        cloth.remove_mass(4);
+        assert_eq!(cloth.coords(0), None);
+        assert_eq!(cloth.index(0, 0), None);
+    }
+
+    #[test]
+    fn shaken_verlet_cloth_stays_stable() {
+        let mut cloth = Cloth::new(8, 8, 0.5, 200.0);
+        cloth.apply_pin_pattern(PinPattern::TopEdge);
+        cloth.set_integrator(Integrator::Verlet);
+        cloth.set_solver_iterations(4);
+        cloth.animate_pins(|time| Vec3::new(0.3 * (10.0 * time).sin(), 0.0, 0.0));
+        for _ in 0..500 {
+            cloth.update(0.01, Vec3::zero(), 0.0);
+            assert!(cloth.is_stable());
+        }
+        let corner = cloth.mass_at(0, 0).unwrap().position;
+        assert!((corner.x - 0.3 * (10.0 * cloth.time()).sin()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn folding_keeps_surface_area_but_shrinks_the_footprint() {
+        let mut cloth = Cloth::new(3, 3, 1.0, 100.0);
+        assert!((cloth.surface_area() - 4.0).abs() < 1e-6);
+        assert_eq!(cloth.surface_area(), cloth.rest_area());
+
+        // Stand the last row up to fold the sheet along row 1
+        for col in 0..3 {
+            cloth.mass_at_mut(2, col).unwrap().position = Vec3::new(col as Scalar, 1.0, 1.0);
+        }
+        let (min, max) = cloth.bounds();
+        assert_eq!((max.x - min.x) * (max.z - min.z), 2.0);
+        assert!((cloth.surface_area() - 4.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn set_stiffness_scales_spring_forces() {
+        let mut cloth = Cloth::new(3, 3, 1.0, 100.0);
+        cloth.masses[8].position += Vec3::new(0.3, 0.2, 0.1);
+        let forces = |cloth: &Cloth| {
+            cloth
+                .all_springs()
+                .map(|(spring, kind)| (kind, spring.force(&cloth.masses).length()))
+                .collect::<Vec<_>>()
+        };
+        let before = forces(&cloth);
+
+        cloth.set_stiffness(SpringStiffness {
+            structural: 200.0,
+            shear: 50.0,
+            bend: 300.0,
+        });
+        for ((kind, old), (_, new)) in before.iter().zip(forces(&cloth)) {
+            let factor = match kind {
+                SpringKind::Structural => 2.0,
+                SpringKind::Shear => 0.5,
+                SpringKind::Bend => 3.0,
+            };
+            assert!((new - old * factor).abs() < 1e-4);
+        }
+
+        cloth.scale_stiffness(0.5);
+        assert!(cloth.structural_springs.iter().all(|spring| spring.stiffness == 100.0));
+    }
+
+    #[test]
+    fn pin_where_pins_the_high_rows_of_a_vertical_cloth() {
+        let mut cloth = Cloth::new_oriented(3, 5, 1.0, 100.0, Orientation::Vertical);
+        cloth.translate(Vec3::new(0.0, 7.0, 0.0));
+        cloth.pin_where(|mass| mass.position.y > 5.0);
+
+        let pinned: Vec<usize> = (0..cloth.masses.len())
+            .filter(|&index| cloth.masses[index].is_pinned())
+            .collect();
+        assert_eq!(pinned, vec![0, 1, 2, 3, 4, 5]);
+
+        let mut region = Cloth::new_oriented(3, 5, 1.0, 100.0, Orientation::Vertical);
+        region.pin_region(Vec3::new(0.5, -4.0, -1.0), Vec3::new(2.0, -3.0, 1.0));
+        let pinned: Vec<usize> = (0..region.masses.len())
+            .filter(|&index| region.masses[index].is_pinned())
+            .collect();
+        assert_eq!(pinned, vec![10, 11, 13, 14]);
+    }
+
+    #[test]
+    fn force_report_gravity_total_matches_the_free_weight() {
+        let mut cloth = Cloth::new(3, 3, 1.0, 100.0);
+        cloth.apply_pin_pattern(PinPattern::TopCorners);
+        cloth.enable_force_report();
+        cloth.update(0.01, Vec3::zero(), 0.0);
+
+        let report = cloth.last_force_report().unwrap();
+        let unpinned = cloth.masses.iter().filter(|mass| !mass.is_pinned()).count();
+        let expected = unpinned as Scalar * DEFAULT_MASS * 9.81;
+        assert!((report.gravity - expected).abs() < 1e-3);
+        assert_eq!(report.wind, 0.0);
+    }
+
+    fn settled_cloth() -> Cloth {
+        let mut cloth = Cloth::new(6, 6, 1.0, 100.0);
+        cloth.apply_pin_pattern(PinPattern::TopEdge);
+        cloth.set_damping(2.0);
+        cloth.set_sleep(0.01, 20);
+        for _ in 0..5000 {
+            if cloth.is_asleep() {
+                break;
+            }
+            cloth.update(0.01, Vec3::zero(), 0.0);
+        }
+        cloth
+    }
+
+    #[test]
+    fn settled_cloth_sleeps_until_an_impulse_wakes_it() {
+        let mut cloth = settled_cloth();
+        assert!(cloth.is_asleep());
+
+        let resting = cloth.positions();
+        for _ in 0..10 {
+            cloth.update(0.01, Vec3::zero(), 0.0);
+        }
+        assert_eq!(cloth.positions(), resting);
+
+        cloth.apply_impulse(30, Vec3::new(0.0, 0.0, 1.0));
+        assert!(!cloth.is_asleep());
+        cloth.update(0.01, Vec3::zero(), 0.0);
+        assert_ne!(cloth.positions(), resting);
+    }
+
+    #[test]
+    fn sleeping_cloth_still_traces_a_row_per_step() {
+        let mut cloth = settled_cloth();
+        let (steps, time) = (cloth.steps(), cloth.time());
+        cloth.enable_trace(30);
+        for _ in 0..10 {
+            cloth.update(0.01, Vec3::zero(), 0.0);
+        }
+
+        assert!(cloth.is_asleep());
+        assert_eq!(cloth.steps(), steps + 10);
+        assert!((cloth.time() - time - 0.1).abs() < 1e-3);
+        assert_eq!(cloth.trace_csv().lines().count(), 1 + 10);
+    }
+
+    #[test]
+    fn force_changes_wake_a_sleeping_cloth() {
+        let mut cloth = settled_cloth();
+        cloth.add_collider(Collider::Sphere {
+            center: Vec3::new(2.5, -3.0, 0.0),
+            radius: 1.0,
+            friction: 0.0,
+            restitution: 0.0,
+            mask: u32::MAX,
+        });
+        assert!(!cloth.is_asleep());
+
+        let mut cloth = settled_cloth();
+        cloth.scale_stiffness(0.5);
+        assert!(!cloth.is_asleep());
+    }
+
+    #[test]
+    fn turbulent_cloth_never_sleeps() {
+        let mut cloth = settled_cloth();
+        cloth.set_turbulent_wind(Vec3::zero(), 0.0, 1.0);
+        for _ in 0..100 {
+            cloth.update(0.01, Vec3::zero(), 0.0);
+        }
+        assert!(!cloth.is_asleep());
+    }
+
+    #[test]
+    fn disabling_shear_lets_a_panel_shear_further() {
+        let shear = |enable_shear: bool| {
+            let mut cloth = Cloth::new(4, 4, 1.0, 100.0);
+            cloth.set_gravity(Vec3::zero());
+            cloth.set_damping(2.0);
+            cloth.set_enable_shear(enable_shear);
+            cloth.apply_pin_pattern(PinPattern::LeftEdge);
+            // Push the free right edge sideways, in the plane of the cloth
+            for _ in 0..300 {
+                cloth.update_with(0.01, Vec3::zero(), 0.0, |mass| {
+                    if mass.position.x > 2.5 {
+                        Vec3::new(0.0, 0.0, 2.0)
+                    } else {
+                        Vec3::zero()
+                    }
+                });
+            }
+            cloth.mass_at(0, 3).unwrap().position.z
+        };
+
+        let (braced, free) = (shear(true), shear(false));
+        assert!(free > 3.0 * braced, "sheared {free} without shear springs, {braced} with");
+    }
+
+    #[test]
+    fn softer_cloth_sags_further() {
+        let deflection = |stiffness: Scalar| {
+            let mut cloth = Cloth::new(6, 6, 0.5, stiffness);
+            cloth.apply_pin_pattern(PinPattern::AllCorners);
+            for _ in 0..500 {
+                cloth.update(0.01, Vec3::zero(), 0.0);
+            }
+            cloth.max_deflection()
+        };
+
+        assert_eq!(Cloth::new(6, 6, 0.5, 100.0).max_deflection(), 0.0);
+        let (stiff, soft) = (deflection(400.0), deflection(50.0));
+        assert!(stiff > 0.0);
+        assert!(soft > stiff);
+    }
+
+    #[test]
+    fn anchored_mass_follows_its_anchor_and_drags_neighbours() {
+        let mut cloth = Cloth::new(3, 3, 1.0, 100.0);
+        cloth.set_gravity(Vec3::zero());
+        cloth.set_anchor(4, cloth.masses[4].position);
+        for step in 1..=100 {
+            let target = Vec3::new(1.0, step as Scalar * 0.01, 1.0);
+            cloth.move_anchor(4, target);
+            cloth.update(0.01, Vec3::zero(), 0.0);
+            assert!(cloth.masses[4].position.approx_eq(&target, 1e-5));
+        }
+        assert!(cloth.masses.iter().all(|mass| mass.position.y > 0.5));
+
+        cloth.clear_anchor(4);
+        assert!(!cloth.masses[4].is_pinned());
+    }
+
+    #[test]
+    fn masked_out_colliders_are_ignored() {
+        let ball = |center: Vec3, mask: u32| Collider::Sphere {
+            center,
+            radius: 1.0,
+            friction: 0.0,
+            restitution: 0.0,
+            mask,
+        };
+        let mut cloth = Cloth::new(2, 1, 4.0, 0.0);
+        cloth.set_gravity(Vec3::zero());
+        cloth.set_collision_mask(0b01);
+        cloth.add_collider(ball(Vec3::new(0.0, -0.5, 0.0), 0b01));
+        cloth.add_collider(ball(Vec3::new(4.0, -0.5, 0.0), 0b10));
+
+        cloth.update(0.01, Vec3::zero(), 0.0);
+        assert!(cloth.masses[0].position.approx_eq(&Vec3::new(0.0, 0.5, 0.0), 1e-5));
+        assert_eq!(cloth.masses[1].position, Vec3::new(4.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn spring_colors_are_disjoint_and_cover_every_spring_once() {
+        let mut cloth = Cloth::new(6, 5, 1.0, 100.0);
+        let colors = cloth.color_springs();
+        let springs: Vec<Spring> = cloth.all_springs().map(|(spring, _)| *spring).collect();
+
+        let mut seen = vec![0; springs.len()];
+        for color in &colors {
+            let mut touched = HashSet::new();
+            for &index in color {
+                seen[index] += 1;
+                assert!(touched.insert(springs[index].a));
+                assert!(touched.insert(springs[index].b));
+            }
+        }
+        assert!(seen.iter().all(|&count| count == 1));
+    }
+
+    #[test]
+    fn upward_force_cancelling_gravity_leaves_a_mass_still() {
+        let mut cloth = Cloth::new(1, 1, 1.0, 100.0);
+        for _ in 0..100 {
+            cloth.update_with(0.01, Vec3::zero(), 0.0, |mass| {
+                Vec3::new(0.0, 9.81 * mass.mass(), 0.0)
+            });
+        }
+        assert_eq!(cloth.masses[0].position, Vec3::zero());
+        assert_eq!(cloth.masses[0].velocity, Vec3::zero());
     }
 }
@@ -0,0 +1,44 @@
+use crate::vec3::{Scalar, Vec3};
+use std::collections::HashMap;
+
+type Cell = (i32, i32, i32);
+
+pub struct SpatialHash {
+    cell_size: Scalar,
+    cells: HashMap<Cell, Vec<usize>>,
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: Scalar) -> Self {
+        SpatialHash {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    pub fn insert(&mut self, index: usize, position: Vec3) {
+        let cell = self.cell(position);
+        self.cells.entry(cell).or_default().push(index);
+    }
+
+    pub fn query(&self, position: Vec3) -> impl Iterator<Item = usize> + '_ {
+        let (x, y, z) = self.cell(position);
+        (-1..=1)
+            .flat_map(move |dx| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| (dx, dy, dz))))
+            .filter_map(move |(dx, dy, dz)| self.cells.get(&(x + dx, y + dy, z + dz)))
+            .flatten()
+            .copied()
+    }
+
+    fn cell(&self, position: Vec3) -> Cell {
+        (
+            (position.x / self.cell_size).floor() as i32,
+            (position.y / self.cell_size).floor() as i32,
+            (position.z / self.cell_size).floor() as i32,
+        )
+    }
+}
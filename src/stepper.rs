@@ -0,0 +1,66 @@
+use crate::vec3::Scalar;
+
+pub struct FixedStepper {
+    pub dt: Scalar,
+    pub max_substeps: usize,
+    accumulator: Scalar,
+}
+
+impl FixedStepper {
+    pub fn new(dt: Scalar, max_substeps: usize) -> Self {
+        FixedStepper {
+            dt,
+            max_substeps,
+            accumulator: 0.0,
+        }
+    }
+
+    pub fn advance(&mut self, elapsed: Scalar) -> usize {
+        self.accumulator += elapsed;
+
+        let mut steps = 0;
+        while self.accumulator >= self.dt && steps < self.max_substeps {
+            self.accumulator -= self.dt;
+            steps += 1;
+        }
+
+        // Drop the backlog we could not catch up on instead of spiralling
+        if steps == self.max_substeps {
+            self.accumulator = self.accumulator.min(self.dt);
+        }
+
+        steps
+    }
+
+    pub fn remainder(&self) -> Scalar {
+        self.accumulator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advancing_a_tenth_of_a_second_runs_five_steps() {
+        let mut stepper = FixedStepper::new(0.02, 10);
+        assert_eq!(stepper.advance(0.1), 5);
+        assert!(stepper.remainder().abs() < 1e-6);
+    }
+
+    #[test]
+    fn leftover_time_carries_to_the_next_call() {
+        let mut stepper = FixedStepper::new(0.02, 10);
+        assert_eq!(stepper.advance(0.03), 1);
+        assert!((stepper.remainder() - 0.01).abs() < 1e-6);
+        assert_eq!(stepper.advance(0.01), 1);
+        assert!(stepper.remainder().abs() < 1e-6);
+    }
+
+    #[test]
+    fn long_frames_are_capped_at_max_substeps() {
+        let mut stepper = FixedStepper::new(0.02, 3);
+        assert_eq!(stepper.advance(1.0), 3);
+        assert!(stepper.remainder() <= 0.02);
+    }
+}
@@ -1,31 +1,73 @@
-#[derive(Debug, Clone, Copy)]
+/// Precision shared by the vector maths and the simulation. Spring parameters
+/// and force maths take it directly, with no conversions at the call site:
+///
+/// ```
+/// use cloth_model::simulation::{Mass, Spring};
+/// use cloth_model::vec3::{Scalar, Vec3};
+///
+/// let stiffness: Scalar = 100.0;
+/// let spring = Spring::new(0, 1, 1.0, stiffness);
+/// let mut masses = vec![
+///     Mass::new(Vec3::zero(), 1.0, false),
+///     Mass::new(Vec3::new(2.0, 0.0, 0.0), 2.0, false),
+/// ];
+/// spring.apply_force(&mut masses);
+///
+/// let extension: Scalar = masses[1].position.x - spring.rest_length;
+/// let force: Vec3 = Vec3::new(-1.0, 0.0, 0.0) * (spring.stiffness * extension);
+/// assert_eq!(masses[1].acceleration, force);
+/// ```
+pub type Scalar = f32;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vec3 {
-    pub x: f32,
-    pub y: f32,
-    pub z: f32,
+    pub x: Scalar,
+    pub y: Scalar,
+    pub z: Scalar,
 }
 
 impl Vec3 {
-    pub fn new(x: f32, y: f32, z: f32) -> Self {
+    pub const ZERO: Vec3 = Vec3 {
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+    };
+
+    pub fn new(x: Scalar, y: Scalar, z: Scalar) -> Self {
         Vec3 { x, y, z }
     }
 
     pub fn zero() -> Self {
-        Vec3 {
-            x: 0.0,
-            y: 0.0,
-            z: 0.0,
-        }
+        Vec3::ZERO
+    }
+
+    pub fn length(&self) -> Scalar {
+        self.length_squared().sqrt()
+    }
+
+    pub fn length_squared(&self) -> Scalar {
+        self.dot(self)
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
     }
 
-    pub fn length(&self) -> f32 {
-        (self.x * self.x + self.y * self.y + self.z * self.z).sqrt()
+    pub fn distance(&self, other: &Vec3) -> Scalar {
+        self.distance_squared(other).sqrt()
+    }
+
+    pub fn distance_squared(&self, other: &Vec3) -> Scalar {
+        let delta = *self - *other;
+        delta.length_squared()
     }
 
     pub fn normalize(&self) -> Self {
-        let len = self.length();
+        let len_squared = self.length_squared();
 
-        if len > 0.0 {
+        if len_squared > 0.0 {
+            let len = len_squared.sqrt();
             Vec3 {
                 x: self.x / len,
                 y: self.y / len,
@@ -36,10 +78,136 @@ impl Vec3 {
         }
     }
 
-    pub fn dot(&self, other: &Vec3) -> f32 {
+    pub fn dot(&self, other: &Vec3) -> Scalar {
         self.x * other.x + self.y * other.y + self.z * other.z
     }
 
+    // The casts are no-ops while Scalar is f32 but keep rendering code working
+    // if the simulation precision changes
+    #[allow(clippy::unnecessary_cast)]
+    pub fn to_f32_array(&self) -> [f32; 3] {
+        [self.x as f32, self.y as f32, self.z as f32]
+    }
+
+    pub fn as_tuple(&self) -> (Scalar, Scalar, Scalar) {
+        (self.x, self.y, self.z)
+    }
+
+    pub fn clamp_length(&self, max: Scalar) -> Vec3 {
+        let length = self.length();
+        if length > max && length > 0.0 {
+            *self * (max / length)
+        } else {
+            *self
+        }
+    }
+
+    pub fn lerp(&self, other: &Vec3, t: Scalar) -> Vec3 {
+        // t is not clamped, values outside [0, 1] extrapolate along the line
+        *self + (*other - *self) * t
+    }
+
+    pub fn project_onto(&self, axis: &Vec3) -> Vec3 {
+        let length_squared = axis.length_squared();
+        if length_squared == 0.0 {
+            return Vec3::zero();
+        }
+        *axis * (self.dot(axis) / length_squared)
+    }
+
+    // normal is expected to be unit length
+    pub fn reflect(&self, normal: &Vec3) -> Vec3 {
+        *self - *normal * (2.0 * self.dot(normal))
+    }
+
+    // Rodrigues' rotation formula. A zero axis leaves the vector unchanged.
+    pub fn rotate_around(&self, axis: &Vec3, angle_rad: Scalar) -> Vec3 {
+        let k = axis.normalize();
+        if k.length_squared() == 0.0 || angle_rad == 0.0 {
+            return *self;
+        }
+
+        let (sin, cos) = angle_rad.sin_cos();
+        *self * cos + k.cross(self) * sin + k * (k.dot(self) * (1.0 - cos))
+    }
+
+    pub fn closest_point_on_segment(&self, a: &Vec3, b: &Vec3) -> Vec3 {
+        let ab = *b - *a;
+        let length_squared = ab.length_squared();
+        if length_squared == 0.0 {
+            return *a;
+        }
+
+        let t = ((*self - *a).dot(&ab) / length_squared).clamp(0.0, 1.0);
+        *a + ab * t
+    }
+
+    pub fn distance_to_segment(&self, a: &Vec3, b: &Vec3) -> Scalar {
+        self.distance(&self.closest_point_on_segment(a, b))
+    }
+
+    pub fn min(&self, other: &Vec3) -> Vec3 {
+        Vec3::new(self.x.min(other.x), self.y.min(other.y), self.z.min(other.z))
+    }
+
+    pub fn max(&self, other: &Vec3) -> Vec3 {
+        Vec3::new(self.x.max(other.x), self.y.max(other.y), self.z.max(other.z))
+    }
+
+    pub fn abs(&self) -> Vec3 {
+        Vec3::new(self.x.abs(), self.y.abs(), self.z.abs())
+    }
+
+    pub fn midpoint(&self, other: &Vec3) -> Vec3 {
+        (*self + *other) * 0.5
+    }
+
+    pub fn approx_eq(&self, other: &Vec3, eps: Scalar) -> bool {
+        (self.x - other.x).abs() <= eps
+            && (self.y - other.y).abs() <= eps
+            && (self.z - other.z).abs() <= eps
+    }
+
+    pub fn component_mul(&self, other: &Vec3) -> Vec3 {
+        Vec3 {
+            x: self.x * other.x,
+            y: self.y * other.y,
+            z: self.z * other.z,
+        }
+    }
+
+    pub fn angle_between(&self, other: &Vec3) -> Scalar {
+        let lengths = self.length() * other.length();
+        if lengths == 0.0 {
+            return 0.0;
+        }
+
+        // Rounding can push the cosine just outside [-1, 1], where acos is NaN
+        (self.dot(other) / lengths).clamp(-1.0, 1.0).acos()
+    }
+
+    // Y is up. Azimuth is measured in the xz-plane from +x towards +z, and
+    // elevation from the xz-plane towards +y, both in radians.
+    pub fn from_spherical(radius: Scalar, azimuth: Scalar, elevation: Scalar) -> Vec3 {
+        Vec3 {
+            x: radius * elevation.cos() * azimuth.cos(),
+            y: radius * elevation.sin(),
+            z: radius * elevation.cos() * azimuth.sin(),
+        }
+    }
+
+    // Inverse of from_spherical, returning (radius, azimuth, elevation) with the
+    // azimuth in [-pi, pi]. The zero vector maps to all zeros.
+    pub fn to_spherical(&self) -> (Scalar, Scalar, Scalar) {
+        let radius = self.length();
+        if radius == 0.0 {
+            return (0.0, 0.0, 0.0);
+        }
+
+        let elevation = (self.y / radius).clamp(-1.0, 1.0).asin();
+        (radius, self.z.atan2(self.x), elevation)
+    }
+
     pub fn cross(&self, other: &Vec3) -> Vec3 {
         Vec3 {
             x: self.y * other.z - self.z * other.y,
@@ -49,8 +217,15 @@ impl Vec3 {
     }
 }
 
+pub fn centroid(points: &[Vec3]) -> Vec3 {
+    if points.is_empty() {
+        return Vec3::zero();
+    }
+    points.iter().copied().sum::<Vec3>() / points.len() as Scalar
+}
+
 use core::iter::Sum;
-use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 
 impl Add for Vec3 {
     type Output = Vec3;
@@ -64,6 +239,26 @@ impl Add for Vec3 {
     }
 }
 
+impl From<[Scalar; 3]> for Vec3 {
+    fn from([x, y, z]: [Scalar; 3]) -> Self {
+        Vec3 { x, y, z }
+    }
+}
+
+impl From<Vec3> for [Scalar; 3] {
+    fn from(v: Vec3) -> Self {
+        [v.x, v.y, v.z]
+    }
+}
+
+impl AddAssign for Vec3 {
+    fn add_assign(&mut self, rhs: Self) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+        self.z += rhs.z;
+    }
+}
+
 impl Sum for Vec3 {
     fn sum<I: Iterator<Item = Vec3>>(iter: I) -> Vec3 {
         iter.fold(Vec3::zero(), |acc, vec| acc + vec)
@@ -82,6 +277,14 @@ impl Sub for Vec3 {
     }
 }
 
+impl SubAssign for Vec3 {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.x -= rhs.x;
+        self.y -= rhs.y;
+        self.z -= rhs.z;
+    }
+}
+
 impl Neg for Vec3 {
     type Output = Self;
 
@@ -94,10 +297,10 @@ impl Neg for Vec3 {
     }
 }
 
-impl Mul<f32> for Vec3 {
+impl Mul<Scalar> for Vec3 {
     type Output = Self;
 
-    fn mul(self, scalar: f32) -> Self {
+    fn mul(self, scalar: Scalar) -> Self {
         Vec3 {
             x: self.x * scalar,
             y: self.y * scalar,
@@ -106,7 +309,23 @@ impl Mul<f32> for Vec3 {
     }
 }
 
-impl Mul<Vec3> for f32 {
+impl Mul<Vec3> for Vec3 {
+    type Output = Self;
+
+    fn mul(self, rhs: Vec3) -> Self {
+        self.component_mul(&rhs)
+    }
+}
+
+impl MulAssign<Scalar> for Vec3 {
+    fn mul_assign(&mut self, scalar: Scalar) {
+        self.x *= scalar;
+        self.y *= scalar;
+        self.z *= scalar;
+    }
+}
+
+impl Mul<Vec3> for Scalar {
     type Output = Vec3;
 
     fn mul(self, rhs: Vec3) -> Self::Output {
@@ -118,10 +337,10 @@ impl Mul<Vec3> for f32 {
     }
 }
 
-impl Div<f32> for Vec3 {
+impl Div<Scalar> for Vec3 {
     type Output = Self;
 
-    fn div(self, scalar: f32) -> Self {
+    fn div(self, scalar: Scalar) -> Self {
         Vec3 {
             x: self.x / scalar,
             y: self.y / scalar,
@@ -129,3 +348,290 @@ impl Div<f32> for Vec3 {
         }
     }
 }
+
+// macroquad's Vec3 is glam::Vec3 from the same glam release, so these also
+// cover rendering
+#[cfg(feature = "glam")]
+impl From<Vec3> for glam::Vec3 {
+    fn from(v: Vec3) -> Self {
+        glam::Vec3::from_array(v.to_f32_array())
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<glam::Vec3> for Vec3 {
+    #[allow(clippy::unnecessary_cast)]
+    fn from(v: glam::Vec3) -> Self {
+        Vec3::new(v.x as Scalar, v.y as Scalar, v.z as Scalar)
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<Vec3> for glam::DVec3 {
+    fn from(v: Vec3) -> Self {
+        glam::DVec3::new(v.x as f64, v.y as f64, v.z as f64)
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<glam::DVec3> for Vec3 {
+    fn from(v: glam::DVec3) -> Self {
+        Vec3::new(v.x as Scalar, v.y as Scalar, v.z as Scalar)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dot_of_orthogonal_vectors_is_zero() {
+        let a = Vec3::new(1.0, 2.0, 0.0);
+        let b = Vec3::new(-2.0, 1.0, 5.0);
+        assert_eq!(a.dot(&b), 0.0);
+    }
+
+    #[test]
+    fn dot_of_parallel_vectors_is_the_product_of_lengths() {
+        let a = Vec3::new(1.0, 2.0, 2.0);
+        assert_eq!(a.dot(&(a * 2.0)), 18.0);
+        assert_eq!(a.dot(&(a * -1.0)), -9.0);
+    }
+
+    #[test]
+    fn dot_with_itself_is_the_squared_length() {
+        let a = Vec3::new(0.3, -1.7, 2.9);
+        assert!((a.dot(&a) - a.length().powi(2)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn cross_of_basis_vectors_is_right_handed() {
+        let x = Vec3::new(1.0, 0.0, 0.0);
+        let y = Vec3::new(0.0, 1.0, 0.0);
+        let z = Vec3::new(0.0, 0.0, 1.0);
+        assert_eq!(x.cross(&y), z);
+        assert_eq!(y.cross(&z), x);
+        assert_eq!(z.cross(&x), y);
+    }
+
+    #[test]
+    fn cross_with_itself_is_zero() {
+        let a = Vec3::new(1.5, -2.0, 3.25);
+        assert_eq!(a.cross(&a), Vec3::zero());
+    }
+
+    #[test]
+    fn compound_operators_match_binary_ones() {
+        let a = Vec3::new(1.0, -2.0, 3.5);
+        let b = Vec3::new(0.25, 4.0, -1.0);
+
+        let mut sum = a;
+        sum += b;
+        assert_eq!(sum, a + b);
+
+        let mut difference = a;
+        difference -= b;
+        assert_eq!(difference, a - b);
+
+        let mut scaled = a;
+        scaled *= 2.5;
+        assert_eq!(scaled, a * 2.5);
+    }
+
+    #[test]
+    fn distance_between_unit_apart_points_is_one() {
+        let a = Vec3::new(1.0, 2.0, 3.0);
+        let b = Vec3::new(1.0, 3.0, 3.0);
+        assert_eq!(a.distance(&b), 1.0);
+        assert_eq!(b.distance(&a), 1.0);
+        assert_eq!(a.distance_squared(&b), 1.0);
+    }
+
+    #[test]
+    fn distance_squared_is_the_square_of_distance() {
+        let a = Vec3::new(0.5, -1.25, 4.0);
+        let b = Vec3::new(-2.0, 3.0, 1.5);
+        assert!((a.distance_squared(&b) - a.distance(&b).powi(2)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn vector_multiplication_is_component_wise() {
+        let a = Vec3::new(2.0, 3.0, 4.0);
+        let b = Vec3::new(1.0, 0.0, 2.0);
+        assert_eq!(a * b, Vec3::new(2.0, 0.0, 8.0));
+        assert_eq!(a.component_mul(&b), a * b);
+        assert_eq!(a * 2.0, Vec3::new(4.0, 6.0, 8.0));
+    }
+
+    #[test]
+    fn lerp_hits_both_ends_and_the_midpoint() {
+        let a = Vec3::new(0.0, 2.0, -4.0);
+        let b = Vec3::new(2.0, 4.0, 4.0);
+        assert_eq!(a.lerp(&b, 0.0), a);
+        assert_eq!(a.lerp(&b, 1.0), b);
+        assert_eq!(a.lerp(&b, 0.5), Vec3::new(1.0, 3.0, 0.0));
+        assert_eq!(a.lerp(&b, 2.0), Vec3::new(4.0, 6.0, 12.0));
+    }
+
+    #[test]
+    fn approx_eq_holds_up_to_epsilon() {
+        let a = Vec3::new(1.0, 1.0, 1.0);
+        assert!(a.approx_eq(&Vec3::new(1.0, 1.0, 1.0625), 0.0625));
+        assert!(!a.approx_eq(&Vec3::new(1.0, 1.0, 1.125), 0.0625));
+        assert!(!a.approx_eq(&Vec3::new(0.875, 1.0, 1.0), 0.0625));
+    }
+
+    #[test]
+    fn array_and_tuple_conversions_round_trip() {
+        let v = Vec3::new(1.5, -2.0, 0.25);
+        let array: [Scalar; 3] = v.into();
+        assert_eq!(array, [1.5, -2.0, 0.25]);
+        assert_eq!(Vec3::from(array), v);
+        assert_eq!(v.to_f32_array(), [1.5, -2.0, 0.25]);
+        assert_eq!(Vec3::from(v.to_f32_array()), v);
+        let (x, y, z) = v.as_tuple();
+        assert_eq!(Vec3::new(x, y, z), v);
+    }
+
+    #[test]
+    fn clamp_length_only_shortens_long_vectors() {
+        let under = Vec3::new(0.0, 3.0, 0.0);
+        assert_eq!(under.clamp_length(5.0), under);
+        let at = Vec3::new(3.0, 4.0, 0.0);
+        assert_eq!(at.clamp_length(5.0), at);
+        let over = Vec3::new(6.0, 8.0, 0.0);
+        assert_eq!(over.clamp_length(5.0), at);
+        assert_eq!(Vec3::ZERO.clamp_length(0.0), Vec3::ZERO);
+    }
+
+    #[test]
+    fn project_onto_keeps_the_component_along_the_axis() {
+        let v = Vec3::new(3.0, 4.0, 5.0);
+        assert_eq!(v.project_onto(&Vec3::new(0.0, 2.0, 0.0)), Vec3::new(0.0, 4.0, 0.0));
+        assert_eq!(v.project_onto(&Vec3::new(1.0, 1.0, 0.0)), Vec3::new(3.5, 3.5, 0.0));
+        assert_eq!(v.project_onto(&Vec3::ZERO), Vec3::ZERO);
+    }
+
+    #[test]
+    fn reflect_bounces_velocity_off_a_horizontal_plane() {
+        let velocity = Vec3::new(2.0, -3.0, 1.0);
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        assert_eq!(velocity.reflect(&up), Vec3::new(2.0, 3.0, 1.0));
+        assert_eq!(velocity.reflect(&up).reflect(&up), velocity);
+    }
+
+    #[test]
+    fn rotating_a_basis_vector_a_quarter_turn() {
+        let x = Vec3::new(1.0, 0.0, 0.0);
+        let z = Vec3::new(0.0, 0.0, 1.0);
+        let rotated = x.rotate_around(&z, std::f32::consts::FRAC_PI_2);
+        assert!(rotated.approx_eq(&Vec3::new(0.0, 1.0, 0.0), 1e-6));
+    }
+
+    #[test]
+    fn full_turn_and_degenerate_rotations_leave_the_vector_alone() {
+        let v = Vec3::new(0.3, -1.2, 2.5);
+        let axis = Vec3::new(1.0, 2.0, -0.5);
+        assert!(v.rotate_around(&axis, std::f32::consts::TAU).approx_eq(&v, 1e-5));
+        assert_eq!(v.rotate_around(&axis, 0.0), v);
+        assert_eq!(v.rotate_around(&Vec3::ZERO, 1.0), v);
+    }
+
+    #[test]
+    fn angle_between_perpendicular_parallel_and_opposite_vectors() {
+        use std::f32::consts::{FRAC_PI_2, PI};
+        let a = Vec3::new(2.0, 0.0, 0.0);
+        assert!((a.angle_between(&Vec3::new(0.0, 3.0, 0.0)) - FRAC_PI_2).abs() < 1e-6);
+        assert_eq!(a.angle_between(&Vec3::new(5.0, 0.0, 0.0)), 0.0);
+        assert!((a.angle_between(&Vec3::new(-1.0, 0.0, 0.0)) - PI).abs() < 1e-6);
+        assert_eq!(a.angle_between(&Vec3::ZERO), 0.0);
+    }
+
+    #[test]
+    fn midpoint_and_centroid() {
+        let a = Vec3::new(0.0, 2.0, -4.0);
+        let b = Vec3::new(2.0, 4.0, 4.0);
+        assert_eq!(a.midpoint(&b), Vec3::new(1.0, 3.0, 0.0));
+
+        let triangle = [a, b, Vec3::new(1.0, 0.0, 3.0)];
+        assert_eq!(centroid(&triangle), Vec3::new(1.0, 2.0, 1.0));
+        assert_eq!(centroid(&[]), Vec3::ZERO);
+    }
+
+    #[test]
+    fn min_max_and_abs_work_per_component() {
+        let a = Vec3::new(-1.0, 2.0, -3.0);
+        let b = Vec3::new(4.0, -5.0, -0.5);
+        assert_eq!(a.min(&b), Vec3::new(-1.0, -5.0, -3.0));
+        assert_eq!(a.max(&b), Vec3::new(4.0, 2.0, -0.5));
+        assert_eq!(a.abs(), Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(b.abs(), Vec3::new(4.0, 5.0, 0.5));
+    }
+
+    #[cfg(feature = "glam")]
+    #[test]
+    fn core_operations_match_glam() {
+        let (a, b) = (Vec3::new(1.0, -2.0, 3.0), Vec3::new(0.5, 4.0, -1.0));
+        let (fa, fb) = (glam::Vec3::from(a), glam::Vec3::from(b));
+        let (da, db) = (glam::DVec3::from(a), glam::DVec3::from(b));
+
+        assert!((a.dot(&b) - fa.dot(fb) as Scalar).abs() < 1e-5);
+        assert!((a.dot(&b) - da.dot(db) as Scalar).abs() < 1e-5);
+        assert!(a.cross(&b).approx_eq(&fa.cross(fb).into(), 1e-5));
+        assert!(a.cross(&b).approx_eq(&da.cross(db).into(), 1e-5));
+        assert!((a.length() - fa.length() as Scalar).abs() < 1e-5);
+        assert!((a.length() - da.length() as Scalar).abs() < 1e-5);
+        assert!(a.normalize().approx_eq(&fa.normalize().into(), 1e-6));
+        assert!(a.normalize().approx_eq(&da.normalize().into(), 1e-6));
+    }
+
+    #[cfg(feature = "glam")]
+    #[test]
+    fn cloth_steps_identically_through_glam() {
+        use crate::simulation::{Cloth, PinPattern};
+
+        let build = || {
+            let mut cloth = Cloth::new(4, 4, 1.0, 100.0);
+            cloth.apply_pin_pattern(PinPattern::TopCorners);
+            cloth
+        };
+        let (mut plain, mut via_glam) = (build(), build());
+        for _ in 0..20 {
+            plain.update(0.01, Vec3::new(0.0, 0.0, 1.0), 2.0);
+            via_glam.update(0.01, Vec3::from(glam::Vec3::new(0.0, 0.0, 1.0)), 2.0);
+            for mass in &mut via_glam.masses {
+                mass.position = glam::Vec3::from(mass.position).into();
+                mass.velocity = glam::DVec3::from(mass.velocity).into();
+            }
+        }
+
+        assert_eq!(plain.positions(), via_glam.positions());
+    }
+
+    #[test]
+    fn length_squared_and_zero_normalize() {
+        let v = Vec3::new(1.5, -2.0, 0.5);
+        assert!((v.length_squared() - v.length().powi(2)).abs() < 1e-6);
+        assert_eq!(Vec3::ZERO.normalize(), Vec3::ZERO);
+        assert!((v.normalize().length() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn spherical_coordinates_round_trip() {
+        for v in [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 2.0),
+            Vec3::new(-1.5, 2.0, 0.5),
+            Vec3::new(0.3, -4.0, -2.2),
+        ] {
+            let (radius, azimuth, elevation) = v.to_spherical();
+            assert!(Vec3::from_spherical(radius, azimuth, elevation).approx_eq(&v, 1e-5));
+        }
+
+        // Azimuth turns from +x towards +z, elevation lifts towards +y
+        let up = Vec3::from_spherical(2.0, 0.0, std::f32::consts::FRAC_PI_2);
+        assert!(up.approx_eq(&Vec3::new(0.0, 2.0, 0.0), 1e-6));
+        let side = Vec3::from_spherical(1.0, std::f32::consts::FRAC_PI_2, 0.0);
+        assert!(side.approx_eq(&Vec3::new(0.0, 0.0, 1.0), 1e-6));
+    }
+}
@@ -0,0 +1,69 @@
+// Steps a fixed cloth and compares the result with a stored reference, so any
+// change to the physics shows up as a failure. After an intentional change,
+// regenerate the reference with:
+//
+//     UPDATE_GOLDEN=1 cargo test --test golden
+
+use cloth_model::simulation::{Cloth, PinPattern};
+use cloth_model::vec3::{Scalar, Vec3};
+use std::fs;
+use std::path::PathBuf;
+
+const TOLERANCE: Scalar = 1e-4;
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{name}.txt"))
+}
+
+fn format_positions(positions: &[Vec3]) -> String {
+    positions
+        .iter()
+        .map(|p| format!("{:?} {:?} {:?}\n", p.x, p.y, p.z))
+        .collect()
+}
+
+fn parse_positions(text: &str) -> Vec<Vec3> {
+    text.lines()
+        .map(|line| {
+            let values: Vec<Scalar> = line
+                .split_whitespace()
+                .map(|value| value.parse().expect("invalid number in golden file"))
+                .collect();
+            Vec3::new(values[0], values[1], values[2])
+        })
+        .collect()
+}
+
+fn check_golden(name: &str, positions: &[Vec3]) {
+    let path = golden_path(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, format_positions(positions)).expect("failed to write golden file");
+        return;
+    }
+
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("missing {}, run with UPDATE_GOLDEN=1", path.display()));
+    let expected = parse_positions(&expected);
+    assert_eq!(positions.len(), expected.len(), "mass count changed");
+    for (index, (actual, expected)) in positions.iter().zip(&expected).enumerate() {
+        assert!(
+            actual.approx_eq(expected, TOLERANCE),
+            "mass {index} moved: expected {expected:?}, got {actual:?}"
+        );
+    }
+}
+
+#[test]
+fn hanging_cloth_in_wind() {
+    let mut cloth = Cloth::new(12, 12, 0.5, 100.0);
+    cloth.apply_pin_pattern(PinPattern::TopCorners);
+    cloth.perturb(0.05, 42);
+    for _ in 0..200 {
+        cloth.update(0.01, Vec3::new(1.0, 0.0, 0.5), 4.0);
+    }
+
+    check_golden("hanging_cloth_in_wind", &cloth.positions());
+}
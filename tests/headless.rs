@@ -0,0 +1,26 @@
+// Runs the simulation through the library alone, with no window or GPU, so it
+// also passes with:
+//
+//     cargo test --no-default-features --test headless
+
+use cloth_model::simulation::{Cloth, PinPattern};
+use cloth_model::vec3::Vec3;
+
+#[test]
+fn energy_stays_bounded_over_500_steps() {
+    let mut cloth = Cloth::new(10, 10, 1.0, 100.0);
+    cloth.apply_pin_pattern(PinPattern::TopCorners);
+
+    // With damping and no wind nothing adds energy, so the total can only fall
+    // from where it started
+    let initial = cloth.kinetic_energy() + cloth.potential_energy();
+    for step in 0..500 {
+        cloth.update(0.01, Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let energy = cloth.kinetic_energy() + cloth.potential_energy();
+        assert!(energy.is_finite(), "energy blew up at step {step}");
+        assert!(energy <= initial + 1e-3, "energy grew to {energy} at step {step}");
+    }
+
+    assert!(cloth.is_stable());
+    assert_eq!(cloth.steps(), 500);
+}